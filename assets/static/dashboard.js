@@ -0,0 +1,6 @@
+// 📊 Boots the admin dashboard: Dioxus compiled to WebAssembly (see dashboard/)
+import init from './dashboard/yeet_dashboard.js';
+
+init().catch(e => {
+    document.getElementById('main').textContent = 'Failed to load the dashboard: ' + e;
+});
@@ -0,0 +1,51 @@
+// 📡 Live tail page: appends the file's new text as it arrives over SSE
+(() => {
+    const MAX_CHARS = 2000000;
+    const log = document.getElementById('log');
+    const status = document.getElementById('status');
+    const pause = document.getElementById('pause');
+    let text = '';
+    let follow = true;
+
+    pause.onclick = () => {
+        follow = !follow;
+        pause.textContent = follow ? '⏸ PAUSE SCROLL' : '▶ RESUME SCROLL';
+        if (follow) log.scrollTop = log.scrollHeight;
+    };
+    // Scrolling up pauses, scrolling back to the bottom resumes
+    log.onscroll = () => {
+        const atBottom = log.scrollHeight - log.scrollTop - log.clientHeight < 20;
+        if (atBottom !== follow) pause.onclick();
+    };
+
+    function append(chunk) {
+        // A bare \r rewinds to the start of the line, like a terminal (progress bars)
+        const parts = chunk.replace(/\r\n/g, '\n').split('\r');
+        text += parts[0];
+        for (const part of parts.slice(1)) {
+            text = text.slice(0, text.lastIndexOf('\n') + 1) + part;
+        }
+        if (text.length > MAX_CHARS) text = text.slice(text.length - MAX_CHARS);
+        log.textContent = text;
+        if (follow) log.scrollTop = log.scrollHeight;
+    }
+
+    const source = new EventSource(document.body.dataset.link + '?follow=sse');
+    // Every (re)connection starts over with the file's last 64KB, so drop
+    // what we have rather than show that stretch twice
+    source.onopen = () => {
+        text = '';
+        append('');
+        status.textContent = '● LIVE';
+        status.className = 'status live';
+    };
+    source.onerror = () => {
+        status.textContent = '○ RECONNECTING';
+        status.className = 'status down';
+    };
+    source.onmessage = (e) => append(JSON.parse(e.data));
+    source.addEventListener('reset', () => {
+        text = '';
+        append('');
+    });
+})();
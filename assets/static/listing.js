@@ -0,0 +1,161 @@
+// 📂 Directory listing: sorting, filtering and share-wide search over the page's
+// JSON data island. Names only ever reach the page through textContent.
+(() => {
+    const listing = JSON.parse(document.getElementById('listing-data').textContent);
+    const $ = id => document.getElementById(id);
+    const rows = $('rows');
+    const ui = {
+        search: '',
+        scope: 'here',
+        grep: '',
+        filter: 'all',
+        sortKey: 'name',
+        sortAsc: true,
+        results: null,
+        truncated: false,
+    };
+
+    function formatBytes(bytes) {
+        const units = ['B', 'KB', 'MB', 'GB'];
+        let i = 0;
+        while (bytes >= 1024 && i < units.length - 1) {
+            bytes /= 1024;
+            i++;
+        }
+        return i === 0 ? `${bytes} B` : `${bytes.toFixed(2)} ${units[i]}`;
+    }
+
+    function el(tag, className, text) {
+        const node = document.createElement(tag);
+        if (className) node.className = className;
+        if (text !== undefined) node.textContent = text;
+        return node;
+    }
+
+    function filteredFiles() {
+        const search = ui.search.toLowerCase();
+        const filtered = (ui.results || listing.files).filter(f => {
+            if (!ui.results && search && !f.name.toLowerCase().includes(search)) return false;
+            if (ui.filter === 'files' && !f.isFile) return false;
+            if (ui.filter === 'dirs' && f.isFile) return false;
+            return true;
+        });
+
+        filtered.sort((a, b) => {
+            const aVal = ui.sortKey === 'size' ? a.sizeBytes : a.name.toLowerCase();
+            const bVal = ui.sortKey === 'size' ? b.sizeBytes : b.name.toLowerCase();
+            return ui.sortAsc ?
+                (aVal < bVal ? -1 : 1) :
+                (aVal > bVal ? -1 : 1);
+        });
+
+        return filtered;
+    }
+
+    function row(file) {
+        const tr = el('tr');
+
+        const name = el('td');
+        const link = el('a');
+        link.href = file.path;
+        link.append(el('span', 'icon', file.icon), el('span', '', file.name));
+        name.append(link);
+        if (file.snippet) name.append(el('span', 'snippet', file.line + ': ' + file.snippet));
+
+        const hash = el('td');
+        if (file.sha256) {
+            const sha = el('span', 'hash', file.sha256.slice(0, 12) + '…');
+            sha.title = file.sha256;
+            sha.onclick = () => navigator.clipboard.writeText(file.sha256);
+            hash.append(sha);
+        } else if (listing.checksums && file.isFile) {
+            hash.append(el('span', 'hash pending', 'hashing…'));
+        }
+
+        const type = el('td');
+        if (file.isFile) {
+            const preview = el('a', '', '👁 PREVIEW');
+            preview.href = file.path + '?inline=1';
+            preview.target = '_blank';
+            preview.title = 'Open in browser';
+            type.append(preview);
+        } else {
+            type.append(el('span', '', 'DIR'));
+        }
+
+        tr.append(name, el('td', '', file.size), hash, type);
+        return tr;
+    }
+
+    function render() {
+        const files = filteredFiles();
+        rows.replaceChildren(...files.map(row));
+
+        for (const indicator of document.querySelectorAll('[data-sort] .sort-indicator')) {
+            const active = indicator.parentElement.dataset.sort === ui.sortKey;
+            indicator.hidden = !active;
+            indicator.textContent = ui.sortAsc ? '▲' : '▼';
+        }
+
+        const total = files.length;
+        const count = files.filter(f => f.isFile).length;
+        const dirs = total - count;
+        const scope = ui.results ? ' ACROSS THE SHARE' + (ui.truncated ? ' (FIRST MATCHES ONLY)' : '') : '';
+        $('stats').textContent = `▸ SHOWING ${total} ITEMS // ${count} FILES // ${dirs} DIRECTORIES${scope}`;
+    }
+
+    async function runSearch() {
+        if (ui.scope !== 'all' || (!ui.search && !ui.grep)) {
+            ui.results = null;
+            return render();
+        }
+        const params = new URLSearchParams({ q: ui.search, grep: ui.grep });
+        const res = await fetch('/api/search?' + params);
+        if (!res.ok) return;
+        const found = await res.json();
+        ui.truncated = found.truncated;
+        ui.results = found.hits.map(hit => ({
+            name: hit.path,
+            path: hit.href,
+            size: hit.type === 'file' ? formatBytes(hit.size) : '-',
+            sizeBytes: hit.size,
+            icon: hit.type === 'file' ? '📄' : '📁',
+            isFile: hit.type === 'file',
+            sha256: hit.sha256,
+            line: hit.line,
+            snippet: hit.snippet,
+        }));
+        render();
+    }
+
+    let timer;
+    function bind(id, searches) {
+        $(id).addEventListener('input', e => {
+            ui[id] = e.target.value;
+            $('grep').hidden = ui.scope !== 'all';
+            render();
+            if (!searches) return;
+            clearTimeout(timer);
+            timer = setTimeout(runSearch, 300);
+        });
+    }
+    bind('search', true);
+    bind('scope', true);
+    bind('grep', true);
+    bind('filter', false);
+
+    for (const th of document.querySelectorAll('[data-sort]')) {
+        th.onclick = () => {
+            if (ui.sortKey === th.dataset.sort) {
+                ui.sortAsc = !ui.sortAsc;
+            } else {
+                ui.sortKey = th.dataset.sort;
+                ui.sortAsc = true;
+            }
+            render();
+        };
+    }
+
+    $('checksums').hidden = !listing.checksums;
+    render();
+})();
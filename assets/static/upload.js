@@ -0,0 +1,99 @@
+// 📥 Drop box page: resumable uploads over tus
+(() => {
+    const drop = document.getElementById('drop');
+    const list = document.getElementById('list');
+
+    // Resumable uploads over tus: chunks survive dropped connections and
+    // tunnel restarts, and a reload picks up where the last attempt stopped
+    const CHUNK = 8 * 1024 * 1024;
+    const b64 = s => btoa(unescape(encodeURIComponent(s)));
+    const sleep = ms => new Promise(r => setTimeout(r, ms));
+
+    function send(method, url, headers, body, onprogress) {
+        return new Promise((resolve, reject) => {
+            const xhr = new XMLHttpRequest();
+            xhr.open(method, url);
+            xhr.setRequestHeader('Tus-Resumable', '1.0.0');
+            for (const [k, v] of Object.entries(headers)) xhr.setRequestHeader(k, v);
+            if (onprogress) xhr.upload.onprogress = onprogress;
+            xhr.onload = () => resolve(xhr);
+            xhr.onerror = () => reject(new Error('connection lost'));
+            xhr.send(body);
+        });
+    }
+
+    async function upload(file) {
+        const item = document.createElement('div');
+        item.className = 'item';
+        const label = document.createElement('div');
+        label.textContent = file.name;
+        const bar = document.createElement('div');
+        bar.className = 'bar';
+        bar.appendChild(document.createElement('div'));
+        item.append(label, bar);
+        list.prepend(item);
+        const progress = done => bar.firstChild.style.width = (100 * done / Math.max(file.size, 1)) + '%';
+        const fail = message => {
+            item.classList.add('failed');
+            label.textContent = '✗ ' + file.name + ' — ' + message;
+        };
+
+        const key = 'yeet-tus:' + [file.name, file.size, file.lastModified].join(':');
+        let url = localStorage.getItem(key);
+        let offset = 0;
+        let retries = 0;
+        while (true) {
+            try {
+                if (url) {
+                    const head = await send('HEAD', url, {});
+                    if (head.status === 200) {
+                        offset = +head.getResponseHeader('Upload-Offset');
+                    } else {
+                        url = null;
+                        localStorage.removeItem(key);
+                    }
+                }
+                if (!url) {
+                    const created = await send('POST', 'tus/', {
+                        'Upload-Length': file.size,
+                        'Upload-Metadata': 'filename ' + b64(file.name),
+                    });
+                    if (created.status !== 201) return fail(created.responseText || created.status);
+                    url = created.getResponseHeader('Location');
+                    localStorage.setItem(key, url);
+                    offset = 0;
+                }
+                while (offset < file.size) {
+                    const res = await send('PATCH', url, {
+                        'Content-Type': 'application/offset+octet-stream',
+                        'Upload-Offset': offset,
+                    }, file.slice(offset, offset + CHUNK), e => progress(offset + e.loaded));
+                    if (res.status === 409) break;  // out of sync: ask the server again
+                    if (res.status !== 204) return fail(res.responseText || res.status);
+                    offset = +res.getResponseHeader('Upload-Offset');
+                    retries = 0;
+                }
+                if (offset < file.size) continue;
+                localStorage.removeItem(key);
+                progress(file.size);
+                item.classList.add('done');
+                label.textContent = '✓ ' + file.name;
+                return;
+            } catch (e) {
+                if (++retries > 10) return fail(e.message);
+                label.textContent = '⟳ ' + file.name + ' — reconnecting…';
+                await sleep(Math.min(30, 2 ** retries) * 1000);
+                label.textContent = file.name;
+            }
+        }
+    }
+
+    document.getElementById('picker').onchange = e => [...e.target.files].forEach(upload);
+    drop.ondragover = e => { e.preventDefault(); drop.classList.add('over'); };
+    drop.ondragleave = () => drop.classList.remove('over');
+    drop.ondrop = e => {
+        e.preventDefault();
+        drop.classList.remove('over');
+        [...e.dataTransfer.files].forEach(upload);
+    };
+})();
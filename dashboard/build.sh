@@ -0,0 +1,56 @@
+#!/bin/sh
+# 📊 Build the admin dashboard to WebAssembly into assets/static/dashboard/,
+# where yeet embeds it. Run it after changing anything under dashboard/ (or
+# src/shared/) and commit the output. Needs the wasm32-unknown-unknown target
+# and the wasm-bindgen CLI of the same version as the wasm-bindgen crate.
+#
+# `build.sh --check` rebuilds into a scratch directory and fails if the result
+# differs from what's committed (CI runs it with the Rust version pinned in
+# .github/workflows/dashboard.yml; other compilers may emit different wasm).
+set -eu
+cd "$(dirname "$0")"
+COMMITTED=../assets/static/dashboard
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+case "${1:-}" in
+    --check) OUT="$tmp/out" ;;
+    "") OUT=$COMMITTED ;;
+    *) echo "usage: $0 [--check]" >&2; exit 2 ;;
+esac
+
+cargo build --release --locked --target wasm32-unknown-unknown
+
+version=$(cargo pkgid wasm-bindgen | sed 's/.*@//')
+if ! wasm-bindgen --version 2>/dev/null | grep -q " $version\$"; then
+    echo "wasm-bindgen $version needed: cargo install wasm-bindgen-cli --version $version --locked" >&2
+    exit 1
+fi
+
+wasm-bindgen --target web --no-typescript --out-dir "$tmp/bindgen" \
+    target/wasm32-unknown-unknown/release/yeet_dashboard.wasm
+
+rm -rf "$OUT"
+mkdir -p "$OUT"
+cp "$tmp/bindgen/yeet_dashboard_bg.wasm" "$OUT/"
+# Snippet directories are named after a hash that changes from build to build:
+# drop it so the embedded names stay put, and keep only the imported snippets
+unhash='s#\./snippets/([^/]+)-[0-9a-f]{16}/#./snippets/\1/#'
+grep -o "'\./snippets/[^']*'" "$tmp/bindgen/yeet_dashboard.js" | tr -d "'" | while read -r snippet; do
+    stable=$(echo "$snippet" | sed -E "$unhash")
+    mkdir -p "$OUT/$(dirname "$stable")"
+    cp "$tmp/bindgen/$snippet" "$OUT/$stable"
+done
+sed -E "$unhash" "$tmp/bindgen/yeet_dashboard.js" > "$OUT/yeet_dashboard.js"
+
+if [ "$OUT" != "$COMMITTED" ]; then
+    if ! diff -r "$OUT" "$COMMITTED" >/dev/null; then
+        echo "assets/static/dashboard is out of date: run dashboard/build.sh and commit the result" >&2
+        diff -rq "$OUT" "$COMMITTED" >&2 || true
+        exit 1
+    fi
+    echo "assets/static/dashboard is up to date"
+    exit 0
+fi
+
+find "$OUT" -type f | sort
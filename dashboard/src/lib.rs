@@ -1,35 +1,44 @@
-// Dioxus web UI components (not currently integrated into main server)
-// Ready for future use when we want to replace the Alpine.js admin dashboard
+// 📊 Dioxus admin dashboard, fed by the daemon's /_yeet/api/stats and /_yeet/api/logs.
+// build.sh compiles it to WebAssembly under assets/static/dashboard/, which
+// yeet embeds and serves at /admin.
 
-use dioxus::prelude::*;
-use crate::shared::{ServerStats, RequestLog};
+use dioxus::{logger::tracing, prelude::*};
+use gloo_net::http::Request;
+use gloo_timers::future::TimeoutFuture;
+use serde::de::DeserializeOwned;
+use wasm_bindgen::prelude::*;
+
+/// The daemon's API types, so both sides always agree on the JSON
+#[path = "../../src/shared/mod.rs"]
+pub mod shared;
+
+use shared::{format_bytes, RequestLog, ServerStats};
+
+/// How often the dashboard refreshes, in milliseconds
+const REFRESH_MS: u32 = 3000;
 
 /// Retro color palette (matching TUI theme)
-#[allow(dead_code)]
 const CYAN: &str = "#00FFFF";
-#[allow(dead_code)]
 const MAGENTA: &str = "#FF00FF";
-#[allow(dead_code)]
 const YELLOW: &str = "#FFFF00";
-#[allow(dead_code)]
 const GREEN: &str = "#00FF9F";
-#[allow(dead_code)]
 const ORANGE: &str = "#FF8000";
-#[allow(dead_code)]
 const DARK_BG: &str = "#14141E";
-#[allow(dead_code)]
 const GRAY: &str = "#808080";
-#[allow(dead_code)]
 const LIGHT_GRAY: &str = "#C0C0C0";
 
+/// Entry point run by the wasm-bindgen loader; renders into `#main`
+#[wasm_bindgen(start)]
+pub fn start() {
+    dioxus::launch(App);
+}
+
 #[component]
 pub fn App() -> Element {
     rsx! {
-        document::Link { rel: "stylesheet", href: asset!("./assets/style.css") }
-
         div {
             class: "container",
-            style: "background: {DARK_BG}; color: {LIGHT_GRAY}; min-height: 100vh; font-family: 'Roboto Mono', monospace; padding: 20px;",
+            style: "background: {DARK_BG}; color: {LIGHT_GRAY}; min-height: 100vh; font-family: 'DejaVu Sans Mono', monospace; padding: 20px;",
 
             // Colorful YEET logo
             Logo {}
@@ -63,19 +72,51 @@ fn Logo() -> Element {
     }
 }
 
+/// Fetch `url` every REFRESH_MS forever, handing each JSON response to
+/// `update` (same-origin, so the admin cookie goes along)
+async fn poll<T: DeserializeOwned>(url: &str, mut update: impl FnMut(T)) {
+    loop {
+        match Request::get(url).send().await {
+            Ok(response) if response.ok() => match response.json::<T>().await {
+                Ok(latest) => update(latest),
+                Err(e) => tracing::warn!("Bad response from {}: {}", url, e),
+            },
+            Ok(response) => tracing::warn!("Failed to fetch {}: HTTP {}", url, response.status()),
+            Err(e) => tracing::warn!("Failed to fetch {}: {}", url, e),
+        }
+        TimeoutFuture::new(REFRESH_MS).await;
+    }
+}
+
+fn format_uptime(secs: u64) -> String {
+    format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+}
+
+fn format_time(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|time| time.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
 #[component]
 fn Stats() -> Element {
-    // TODO: Fetch real stats from /api/stats endpoint
-    let _stats = use_signal(|| ServerStats {
-        uptime_secs: 7515,
-        total_requests: 1247,
-        total_bytes_sent: 163_680_051,
-        current_speed_bps: 2_200_000,
-        active_connections: 8,
-        unique_ips: 42,
-        requests_per_minute: 24,
+    let mut stats = use_signal(|| None::<ServerStats>);
+    let mut peak_rpm = use_signal(|| 1u32);
+
+    use_future(move || {
+        poll("/_yeet/api/stats", move |latest: ServerStats| {
+            if latest.requests_per_minute > peak_rpm() {
+                peak_rpm.set(latest.requests_per_minute);
+            }
+            stats.set(Some(latest));
+        })
     });
 
+    let current = stats();
+    // Until the first response arrives every card shows a dash
+    let show = |value: fn(&ServerStats) -> String| current.as_ref().map_or("-".to_string(), value);
+    let rpm = current.as_ref().map_or(0, |s| s.requests_per_minute);
+
     rsx! {
         div {
             class: "stats-grid",
@@ -88,27 +129,39 @@ fn Stats() -> Element {
 
             StatCard {
                 label: "UPTIME",
-                value: "2h 15m",
+                value: show(|s| format_uptime(s.uptime_secs)),
                 color: CYAN,
             }
 
             StatCard {
                 label: "REQUESTS",
-                value: "1,247",
+                value: show(|s| s.total_requests.to_string()),
                 color: MAGENTA,
             }
 
             StatCard {
                 label: "BANDWIDTH",
-                value: "156.2 MB",
+                value: show(|s| format_bytes(s.total_bytes_sent)),
                 color: YELLOW,
             }
 
             StatCard {
                 label: "SPEED",
-                value: "2.1 MB/s",
+                value: show(|s| format!("{}/s", format_bytes(s.current_speed_bps))),
                 color: GREEN,
             }
+
+            StatCard {
+                label: "CONNECTIONS",
+                value: show(|s| s.active_connections.to_string()),
+                color: ORANGE,
+            }
+
+            StatCard {
+                label: "UNIQUE IPs",
+                value: show(|s| s.unique_ips.to_string()),
+                color: CYAN,
+            }
         }
 
         // Progress bar for requests/min
@@ -117,11 +170,12 @@ fn Stats() -> Element {
 
             div {
                 style: "color: {LIGHT_GRAY}; margin-bottom: 10px;",
-                "ACTIVITY: 24 requests/min"
+                "ACTIVITY: {rpm} requests/min (peak {peak_rpm})"
             }
 
+            // Relative to the busiest minute seen so far
             ProgressBar {
-                percent: 0.75,
+                percent: rpm as f64 / peak_rpm() as f64,
                 color: ORANGE,
             }
         }
@@ -189,27 +243,9 @@ fn ProgressBar(percent: f64, color: String) -> Element {
 
 #[component]
 fn LiveLog() -> Element {
-    // TODO: Fetch real logs from /api/logs endpoint
-    let _logs = use_signal(|| vec![
-        RequestLog {
-            timestamp: 1234567890,
-            method: "GET".to_string(),
-            path: "/cat.jpg".to_string(),
-            status: 200,
-            size_bytes: 2_400_000,
-            user_agent: "Chrome".to_string(),
-            ip: "192.168.1.5".to_string(),
-        },
-        RequestLog {
-            timestamp: 1234567888,
-            method: "GET".to_string(),
-            path: "/cat.jpg".to_string(),
-            status: 200,
-            size_bytes: 2_400_000,
-            user_agent: "Safari".to_string(),
-            ip: "10.0.1.23".to_string(),
-        },
-    ]);
+    let mut logs = use_signal(Vec::<RequestLog>::new);
+
+    use_future(move || poll("/_yeet/api/logs", move |latest: Vec<RequestLog>| logs.set(latest)));
 
     rsx! {
         div {
@@ -227,10 +263,10 @@ fn LiveLog() -> Element {
                     border-radius: 8px;
                     padding: 20px;
                     background: rgba(255, 255, 255, 0.03);
-                    font-family: 'Roboto Mono', monospace;
+                    font-family: 'DejaVu Sans Mono', monospace;
                 ",
 
-                for log in _logs() {
+                for log in logs() {
                     LogEntry { log: log.clone() }
                 }
             }
@@ -245,6 +281,8 @@ fn LogEntry(log: RequestLog) -> Element {
         300..=399 => YELLOW,
         _ => ORANGE,
     };
+    let time = format_time(log.timestamp);
+    let size = format_bytes(log.size_bytes);
 
     rsx! {
         div {
@@ -257,11 +295,11 @@ fn LogEntry(log: RequestLog) -> Element {
                 font-size: 13px;
             ",
 
-            span { style: "color: {GRAY};", "00:23:45" }
+            span { style: "color: {GRAY};", "{time}" }
             span { style: "color: {CYAN};", "{log.method}" }
             span { style: "color: {LIGHT_GRAY};", "{log.path}" }
             span { style: "color: {status_color};", "{log.status}" }
-            span { style: "color: {YELLOW};", "2.4MB" }
+            span { style: "color: {YELLOW};", "{size}" }
             span { style: "color: {MAGENTA};", "{log.user_agent}" }
             span { style: "color: {GRAY};", "{log.ip}" }
         }
@@ -1,7 +1,9 @@
 // 🎮 Module declarations - TUI and Shared types
 mod tui;
-mod web;
 mod shared;
+mod server;
+mod state;
+mod tunnel;
 
 use anyhow::Result;
 use clap::Parser;
@@ -19,20 +21,73 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::{
-    io,
+    io::{self, IsTerminal},
+    net::SocketAddr,
     path::PathBuf,
-    process::{Command, Stdio},
+    sync::Arc,
     thread,
     time::{Duration, Instant},
-    fs,
 };
 use tokio::runtime::Runtime;
-use axum::Router;
-use serde::{Serialize, Deserialize};
+use axum::{extract::State, Router};
 use nix::unistd::{fork, ForkResult, setsid};
+use shared::format_bytes;
+use state::TunnelState;
 
-// Spawn a daemon process that runs server + cloudflared
-fn spawn_daemon(file_path: PathBuf, port: u16) -> Result<u32> {
+/// How long in-flight requests get to finish once the daemon shuts down
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Everything the forked daemon needs to run one share
+struct DaemonConfig {
+    share_id: String,
+    file_path: PathBuf,
+    port: u16,
+    symlinks: server::paths::SymlinkPolicy,
+    auth: server::auth::AuthConfig,
+    expiry: server::expiry::ExpiryPolicy,
+    tunnel: Arc<dyn tunnel::TunnelProvider>,
+    /// Drop-box mode: accept uploads into `file_path` instead of serving it
+    receive: Option<server::upload::UploadConfig>,
+    /// WebDAV at /dav, when enabled
+    webdav: Option<server::webdav::WebDavConfig>,
+    /// Content-hash ETags instead of size+mtime
+    hash_etags: bool,
+    /// Reverse-proxy mode: forward everything to this local server
+    proxy: Option<server::proxy::Target>,
+    /// Share piped input (`yeet -`); `file_path` is just the download name
+    stdin: Option<server::stdin::StdinConfig>,
+    /// Single file only: add a live `tail -f` view of it (--follow)
+    follow: bool,
+    /// Hash shared files in the background: `Some(with_blake3)` when enabled
+    checksums: Option<bool>,
+    /// Fingerprint of the share's options, see `share_settings`
+    settings: String,
+}
+
+/// Hash of every option that changes what a share exposes, how long it lives
+/// or where it's published, so a share is only reused for the same options.
+/// Keyed with a per-install secret since the passwords are part of it.
+fn share_settings(cli: &Cli) -> String {
+    let options = format!(
+        "{:?}",
+        (
+            &cli.password,
+            cli.secret_link,
+            &cli.admin_password,
+            (cli.expire, cli.idle_timeout, cli.max_downloads),
+            (cli.webdav, cli.webdav_write),
+            cli.symlinks,
+            (cli.hash_etags, cli.no_checksums, cli.blake3),
+            (cli.max_upload_size, cli.overwrite),
+            (cli.tunnel, &cli.tunnel_name, &cli.hostname, &cli.public_url),
+            (&cli.ssh_target, cli.ssh_remote_port),
+        )
+    );
+    blake3::keyed_hash(&state::settings_key(), options.as_bytes()).to_hex().to_string()
+}
+
+// Spawn a daemon process that runs server + tunnel
+fn spawn_daemon(config: DaemonConfig) -> Result<u32> {
     match unsafe { fork() } {
         Ok(ForkResult::Parent { child }) => {
             // Parent process - return daemon PID
@@ -56,12 +111,15 @@ fn spawn_daemon(file_path: PathBuf, port: u16) -> Result<u32> {
             use std::os::unix::io::AsRawFd;
             use nix::unistd::dup2;
             let null_fd = dev_null.as_raw_fd();
-            dup2(null_fd, 0).ok(); // stdin
+            // `yeet -` keeps the pipe: it's what's being shared
+            if config.stdin.is_none() {
+                dup2(null_fd, 0).ok(); // stdin
+            }
             dup2(null_fd, 1).ok(); // stdout
             dup2(null_fd, 2).ok(); // stderr
 
-            // Run server and cloudflared
-            run_daemon_server(file_path, port);
+            // Run server and tunnel
+            run_daemon_server(config);
 
             // Should never reach here
             std::process::exit(0);
@@ -72,369 +130,178 @@ fn spawn_daemon(file_path: PathBuf, port: u16) -> Result<u32> {
     }
 }
 
-// Helper to format bytes into human-readable size
-fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
+// API Handler: /api/stats - Returns server statistics
+async fn api_stats_handler(
+    State(stats): State<Arc<server::stats::StatsTracker>>,
+) -> axum::Json<shared::ServerStats> {
+    axum::Json(stats.snapshot())
 }
 
-// API Handler: /api/stats - Returns server statistics
-async fn api_stats_handler() -> axum::Json<shared::ServerStats> {
-    use axum::Json;
-    // TODO: Replace with real stats tracking
-    Json(shared::ServerStats {
-        uptime_secs: 3600,
-        total_requests: 42,
-        total_bytes_sent: 5_000_000,
-        current_speed_bps: 1_200_000,
-        active_connections: 2,
-        unique_ips: 7,
-        requests_per_minute: 15,
-    })
+// API Handler: /api/logs - Returns recent request logs (newest first)
+async fn api_logs_handler(
+    State(stats): State<Arc<server::stats::StatsTracker>>,
+) -> axum::Json<Vec<shared::RequestLog>> {
+    axum::Json(stats.logs())
 }
 
-// API Handler: /api/logs - Returns recent request logs
-async fn api_logs_handler() -> axum::Json<Vec<shared::RequestLog>> {
-    use axum::Json;
-    // TODO: Replace with real log tracking
-    Json(vec![
-        shared::RequestLog {
-            timestamp: 1234567890,
-            method: "GET".to_string(),
-            path: "/cat.jpg".to_string(),
-            status: 200,
-            size_bytes: 2_400_000,
-            user_agent: "Chrome".to_string(),
-            ip: "192.168.1.5".to_string(),
-        },
-        shared::RequestLog {
-            timestamp: 1234567888,
-            method: "GET".to_string(),
-            path: "/cat.jpg".to_string(),
-            status: 200,
-            size_bytes: 2_400_000,
-            user_agent: "Safari".to_string(),
-            ip: "10.0.1.23".to_string(),
-        },
-    ])
+/// The dashboard and its API, always under /_yeet/ and, unless a proxied
+/// app owns every other path, at /admin and /api/* too
+fn admin_routes(proxied: bool) -> Router<Arc<server::stats::StatsTracker>> {
+    let mut routes = Router::new();
+    let prefixes: &[&str] = if proxied { &[server::auth::NAMESPACE] } else { &[server::auth::NAMESPACE, ""] };
+    for prefix in prefixes {
+        routes = routes
+            .route(&format!("{}/api/stats", prefix), axum::routing::get(api_stats_handler))
+            .route(&format!("{}/api/logs", prefix), axum::routing::get(api_logs_handler))
+            .route(&format!("{}/admin", prefix), axum::routing::get(admin_handler));
+    }
+    routes
 }
 
 // Admin Dashboard Handler: /admin - Retro-styled stats dashboard
-async fn admin_handler() -> axum::response::Html<String> {
-    use axum::response::Html;
-    Html(r#"<!DOCTYPE html>
+async fn admin_handler() -> axum::response::Response {
+    use axum::http::header;
+    // The Dioxus dashboard (dashboard/) renders into #main once its wasm loads
+    let page = r#"<!DOCTYPE html>
 <html>
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>YEET.SH // ADMIN DASHBOARD</title>
-    <link href="https://fonts.googleapis.com/css2?family=Roboto+Mono:wght@400;700&display=swap" rel="stylesheet">
-    <script defer src="https://cdn.jsdelivr.net/npm/alpinejs@3.x.x/dist/cdn.min.js"></script>
-    <style>
-        * { margin: 0; padding: 0; box-sizing: border-box; }
-        body {
-            background: #14141E;
-            color: #C0C0C0;
-            font-family: 'Roboto Mono', monospace;
-            padding: 2rem;
-            min-height: 100vh;
-        }
-        .container { max-width: 1400px; margin: 0 auto; }
-        .header {
-            text-align: center;
-            margin-bottom: 3rem;
-            padding: 2rem;
-            border: 3px solid #00FFFF;
-            background: rgba(0, 255, 255, 0.05);
-            position: relative;
-        }
-        .logo {
-            font-size: 3rem;
-            font-weight: bold;
-            background: linear-gradient(90deg, #FF00FF 0%, #00FFFF 25%, #FFFF00 50%, #00FF9F 75%, #FF00FF 100%);
-            -webkit-background-clip: text;
-            -webkit-text-fill-color: transparent;
-            background-clip: text;
-            text-shadow: 0 0 30px rgba(255, 0, 255, 0.5);
-            margin-bottom: 0.5rem;
-            animation: glow 2s infinite;
-        }
-        @keyframes glow {
-            0%, 100% { filter: brightness(1); }
-            50% { filter: brightness(1.3); }
-        }
-        .subtitle {
-            color: #808080;
-            font-size: 1rem;
-        }
-        .stats-grid {
-            display: grid;
-            grid-template-columns: repeat(auto-fit, minmax(250px, 1fr));
-            gap: 1.5rem;
-            margin-bottom: 3rem;
-        }
-        .stat-card {
-            border: 2px solid;
-            padding: 1.5rem;
-            background: rgba(255, 255, 255, 0.03);
-            transition: all 0.3s;
-            position: relative;
-            overflow: hidden;
-        }
-        .stat-card::before {
-            content: '';
-            position: absolute;
-            top: 0;
-            left: 0;
-            right: 0;
-            height: 3px;
-            background: currentColor;
-            animation: scan 2s infinite;
-        }
-        @keyframes scan {
-            0%, 100% { opacity: 0.3; }
-            50% { opacity: 1; }
-        }
-        .stat-card:hover {
-            transform: translateY(-5px);
-            box-shadow: 0 10px 30px rgba(0, 255, 255, 0.3);
-        }
-        .stat-label {
-            font-size: 0.8rem;
-            margin-bottom: 0.5rem;
-            opacity: 0.8;
-        }
-        .stat-value {
-            font-size: 2rem;
-            font-weight: bold;
-        }
-        .logs-section {
-            border: 2px solid #808080;
-            padding: 2rem;
-            background: rgba(255, 255, 255, 0.02);
-        }
-        .logs-header {
-            color: #00FFFF;
-            font-size: 1.5rem;
-            margin-bottom: 1.5rem;
-            display: flex;
-            align-items: center;
-            gap: 0.5rem;
-        }
-        .log-entry {
-            display: grid;
-            grid-template-columns: 80px 60px 1fr 60px 100px 150px 120px;
-            gap: 1rem;
-            padding: 0.75rem 0;
-            border-bottom: 1px solid rgba(255, 255, 255, 0.1);
-            font-size: 0.85rem;
-        }
-        .log-header {
-            font-weight: bold;
-            color: #00D4FF;
-        }
-        .status-200 { color: #00FF9F; }
-        .status-300 { color: #FFFF00; }
-        .status-400 { color: #FF8000; }
-    </style>
+    <link href="{fonts}" rel="stylesheet">
+    <link href="{css}" rel="stylesheet">
+    <script type="module" src="{js}"></script>
 </head>
-<body x-data="dashboard()" x-init="init()">
-    <div class="container">
-        <div class="header">
-            <div class="logo">██ YEET.SH ██</div>
-            <div class="subtitle">🚀 ADMIN DASHBOARD // REAL-TIME SERVER METRICS</div>
-        </div>
-
-        <div class="stats-grid">
-            <div class="stat-card" style="border-color: #00FFFF; color: #00FFFF;">
-                <div class="stat-label">UPTIME</div>
-                <div class="stat-value" x-text="stats.uptime"></div>
-            </div>
-            <div class="stat-card" style="border-color: #FF00FF; color: #FF00FF;">
-                <div class="stat-label">REQUESTS</div>
-                <div class="stat-value" x-text="stats.total_requests"></div>
-            </div>
-            <div class="stat-card" style="border-color: #FFFF00; color: #FFFF00;">
-                <div class="stat-label">BANDWIDTH</div>
-                <div class="stat-value" x-text="stats.bandwidth"></div>
-            </div>
-            <div class="stat-card" style="border-color: #00FF9F; color: #00FF9F;">
-                <div class="stat-label">SPEED</div>
-                <div class="stat-value" x-text="stats.speed"></div>
-            </div>
-            <div class="stat-card" style="border-color: #FF8000; color: #FF8000;">
-                <div class="stat-label">CONNECTIONS</div>
-                <div class="stat-value" x-text="stats.active_connections"></div>
-            </div>
-            <div class="stat-card" style="border-color: #00D4FF; color: #00D4FF;">
-                <div class="stat-label">UNIQUE IPs</div>
-                <div class="stat-value" x-text="stats.unique_ips"></div>
-            </div>
-            <div class="stat-card" style="border-color: #C0C0C0; color: #C0C0C0;">
-                <div class="stat-label">REQ/MIN</div>
-                <div class="stat-value" x-text="stats.requests_per_minute"></div>
-            </div>
-        </div>
-
-        <div class="logs-section">
-            <div class="logs-header">📡 LIVE REQUEST LOG</div>
-            <div class="log-entry log-header">
-                <div>TIME</div>
-                <div>METHOD</div>
-                <div>PATH</div>
-                <div>STATUS</div>
-                <div>SIZE</div>
-                <div>USER AGENT</div>
-                <div>IP</div>
-            </div>
-            <template x-for="log in logs" :key="log.timestamp">
-                <div class="log-entry">
-                    <div style="color: #808080;" x-text="formatTime(log.timestamp)"></div>
-                    <div style="color: #00FFFF;" x-text="log.method"></div>
-                    <div style="color: #C0C0C0;" x-text="log.path"></div>
-                    <div :class="'status-' + Math.floor(log.status/100) + '00'" x-text="log.status"></div>
-                    <div style="color: #FFFF00;" x-text="formatBytes(log.size_bytes)"></div>
-                    <div style="color: #FF00FF;" x-text="log.user_agent"></div>
-                    <div style="color: #808080;" x-text="log.ip"></div>
-                </div>
-            </template>
-        </div>
-    </div>
-
-    <script>
-        function dashboard() {
-            return {
-                stats: {
-                    uptime: '-',
-                    total_requests: '-',
-                    bandwidth: '-',
-                    speed: '-',
-                    active_connections: '-',
-                    unique_ips: '-',
-                    requests_per_minute: '-'
-                },
-                logs: [],
-
-                async init() {
-                    await this.fetchStats();
-                    await this.fetchLogs();
-                    // Refresh every 3 seconds
-                    setInterval(() => this.fetchStats(), 3000);
-                    setInterval(() => this.fetchLogs(), 3000);
-                },
-
-                async fetchStats() {
-                    try {
-                        const res = await fetch('/api/stats');
-                        const data = await res.json();
-                        this.stats = {
-                            uptime: this.formatUptime(data.uptime_secs),
-                            total_requests: data.total_requests.toLocaleString(),
-                            bandwidth: this.formatBytes(data.total_bytes_sent),
-                            speed: this.formatBytes(data.current_speed_bps) + '/s',
-                            active_connections: data.active_connections,
-                            unique_ips: data.unique_ips,
-                            requests_per_minute: data.requests_per_minute
-                        };
-                    } catch (e) {
-                        console.error('Failed to fetch stats:', e);
-                    }
-                },
-
-                async fetchLogs() {
-                    try {
-                        const res = await fetch('/api/logs');
-                        this.logs = await res.json();
-                    } catch (e) {
-                        console.error('Failed to fetch logs:', e);
-                    }
-                },
-
-                formatUptime(secs) {
-                    const h = Math.floor(secs / 3600);
-                    const m = Math.floor((secs % 3600) / 60);
-                    return `${h}h ${m}m`;
-                },
-
-                formatBytes(bytes) {
-                    if (bytes >= 1e9) return (bytes / 1e9).toFixed(1) + ' GB';
-                    if (bytes >= 1e6) return (bytes / 1e6).toFixed(1) + ' MB';
-                    if (bytes >= 1e3) return (bytes / 1e3).toFixed(1) + ' KB';
-                    return bytes + ' B';
-                },
-
-                formatTime(timestamp) {
-                    const date = new Date(timestamp * 1000);
-                    return date.toLocaleTimeString('en-US', { hour12: false });
-                }
-            };
-        }
-    </script>
+<body>
+    <div id="main"></div>
+    <noscript>The dashboard needs JavaScript and WebAssembly; /_yeet/api/stats and /_yeet/api/logs serve the same data as JSON.</noscript>
 </body>
-</html>"#.to_string())
+</html>"#
+        .replace("{fonts}", &server::assets::url("yeet.css"))
+        .replace("{css}", &server::assets::url("dashboard.css"))
+        .replace("{js}", &server::assets::url("dashboard.js"));
+    axum::response::Response::builder()
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::CONTENT_SECURITY_POLICY, server::assets::ADMIN_CSP)
+        .body(axum::body::Body::from(page))
+        .unwrap()
 }
 
-// Run server + cloudflared in daemon mode (called from forked child)
-fn run_daemon_server(file_path: PathBuf, port: u16) {
+// Run server + tunnel in daemon mode (called from forked child)
+fn run_daemon_server(config: DaemonConfig) {
     use std::process;
 
-    let daemon_pid = process::id();
+    let DaemonConfig { share_id, file_path, port, symlinks, auth, expiry, tunnel, receive, webdav, hash_etags, proxy, stdin, follow, checksums, settings } = config;
+
+    // Register the share right away so `yeet list` sees it while the tunnel comes up
+    let mut state = TunnelState::new(share_id.clone(), process::id(), port, file_path.to_string_lossy().to_string());
+    state.expires_at = expiry.expire.map(|ttl| state.created_at + ttl.as_secs());
+    state.idle_timeout_secs = expiry.idle_timeout.map(|d| d.as_secs());
+    state.max_downloads = expiry.max_downloads;
+    state.tunnel = tunnel.name().to_string();
+    state.receive = receive.is_some();
+    state.proxy = proxy.is_some();
+    state.follow = follow;
+    state.settings = Some(settings);
+    let state = state::StateHandle::new(state);
+    if hash_etags {
+        server::files::enable_content_etags();
+    }
+
     let rt = Runtime::new().expect("Failed to create runtime");
 
     rt.block_on(async move {
         use axum::response::Response;
         use axum::body::Body;
-        use axum::http::{header, StatusCode};
-
-        let is_dir = file_path.is_dir();
-
-        let app = if is_dir {
+        use axum::http::StatusCode;
+
+        // A stdin share's path is only a name and must never hit the filesystem
+        let is_dir = stdin.is_none() && file_path.is_dir();
+        // Only plain file and directory shares are hashed; the rest have nothing stable to hash
+        let hashed = proxy.is_none() && stdin.is_none() && receive.is_none() && !follow;
+        if let (Some(with_blake3), true) = (checksums, hashed) {
+            server::checksum::enable(with_blake3);
+        }
+        // Directories, proxied servers and live views are shared from `/`, other files by name
+        let at_root = is_dir || proxy.is_some() || follow;
+        let stats = Arc::new(server::stats::StatsTracker::new(tunnel.client_ip_header()));
+        let expiry = Arc::new(server::expiry::Expiry::new(expiry));
+        let shutdown = tokio_util::sync::CancellationToken::new();
+
+        let app = if let Some(target) = proxy {
+            // Reverse proxy: everything but yeet's namespaced routes goes to the local
+            // server, which does its own compression and caching
+            Router::new()
+                .merge(admin_routes(true))
+                .merge(server::assets::router())
+                .merge(server::proxy::router(target, tunnel.client_ip_header()))
+                .layer(axum::middleware::from_fn_with_state(expiry.clone(), server::expiry::enforce))
+                .layer(axum::middleware::from_fn_with_state(stats.clone(), server::stats::track))
+                .with_state(stats.clone())
+        } else if let Some(stdin) = stdin {
+            // Piped input: one download route, no filesystem access at all
+            let stdin_routes = server::stdin::router(stdin, &share_id, state.clone(), shutdown.clone())
+                .expect("Failed to create stdin spool");
+            Router::new()
+                .merge(admin_routes(false))
+                .merge(server::assets::router())
+                .merge(stdin_routes)
+                .layer(server::compression::layer())
+                .layer(axum::middleware::map_response(server::compression::adjust_for_encoding))
+                .layer(axum::middleware::from_fn_with_state(expiry.clone(), server::expiry::enforce))
+                .layer(axum::middleware::from_fn_with_state(stats.clone(), server::stats::track))
+                .with_state(stats.clone())
+        } else if let Some(upload) = receive {
+            // Drop box: upload page only, nothing already in the directory is exposed
+            Router::new()
+                .merge(admin_routes(false))
+                .merge(server::assets::router())
+                .merge(server::upload::router(upload, state.clone()))
+                .layer(server::compression::layer())
+                .layer(axum::middleware::map_response(server::compression::adjust_for_encoding))
+                .layer(axum::middleware::from_fn_with_state(expiry.clone(), server::expiry::enforce))
+                .layer(axum::middleware::from_fn_with_state(stats.clone(), server::stats::track))
+                .with_state(stats.clone())
+        } else if is_dir {
             // Serve directory with sick retro UI
-            let dir_path = file_path.clone();
+            let safe_root = server::paths::SafeRoot::new(&file_path, symlinks)
+                .expect("Failed to resolve shared directory");
+            // --webdav mounts the same tree, under the same path rules, at /dav
+            let dav_routes = match webdav {
+                Some(config) => server::webdav::router(safe_root.clone(), config),
+                None => Router::new(),
+            };
+            let checksum_routes = server::checksum::router(safe_root.clone());
+            let api_routes = server::listing::api_router(safe_root.clone())
+                .merge(server::search::router(safe_root.clone()));
+            server::checksum::precompute(safe_root.clone());
             let index_handler = move |req: axum::extract::Request| {
-                let base_path = dir_path.clone();
+                let safe_root = safe_root.clone();
                 async move {
                     // Get path from URI
                     let req_path = req.uri().path();
 
-                    // Construct full path
-                    let full_path = if req_path.is_empty() || req_path == "/" {
-                        base_path.clone()
-                    } else {
-                        base_path.join(req_path.trim_start_matches('/'))
+                    // Resolve against the shared root (rejects traversal and symlink escapes)
+                    let full_path = match safe_root.resolve(req_path) {
+                        Ok(path) => path,
+                        Err(server::paths::PathError::Forbidden) => {
+                            return Response::builder()
+                                .status(StatusCode::FORBIDDEN)
+                                .body(Body::from("Forbidden"))
+                                .unwrap();
+                        }
+                        Err(server::paths::PathError::NotFound) => {
+                            return Response::builder()
+                                .status(StatusCode::NOT_FOUND)
+                                .body(Body::from("Not found"))
+                                .unwrap();
+                        }
                     };
 
                     // If it's a file, serve it
                     if full_path.is_file() {
-                        match tokio::fs::read(&full_path).await {
-                            Ok(contents) => {
-                                return Response::builder()
-                                    .status(StatusCode::OK)
-                                    .header(header::CONTENT_TYPE, "application/octet-stream")
-                                    .header(header::CONTENT_DISPOSITION,
-                                        format!("attachment; filename=\"{}\"",
-                                            full_path.file_name().unwrap().to_string_lossy()))
-                                    .body(Body::from(contents))
-                                    .unwrap();
-                            }
-                            Err(_) => {
-                                return Response::builder()
-                                    .status(StatusCode::NOT_FOUND)
-                                    .body(Body::from("File not found"))
-                                    .unwrap();
-                            }
-                        }
+                        let inline = server::content_type::wants_inline(req.uri().query());
+                        return server::files::serve_file(&full_path, req.headers(), inline).await;
                     }
 
                     // If not a directory, 404
@@ -445,474 +312,306 @@ fn run_daemon_server(file_path: PathBuf, port: u16) {
                             .unwrap();
                     }
 
-                    let path = full_path;
-                    let dir_name = path.file_name().unwrap().to_string_lossy().to_string();
-                    let current_path = req_path.to_string();
-                    let mut files = Vec::new();
-
-                    if let Ok(entries) = std::fs::read_dir(&path) {
-                        for entry in entries.flatten() {
-                            if let (Ok(name), Ok(metadata)) = (entry.file_name().into_string(), entry.metadata()) {
-                                let size = metadata.len();
-                                let is_file = metadata.is_file();
-                                files.push((name, size, is_file));
-                            }
-                        }
+                    // ?download=zip|tar.gz streams the whole subtree as one archive
+                    if let Some(format) = server::archive::ArchiveFormat::from_query(req.uri().query()) {
+                        return server::archive::archive_response(full_path, safe_root, format);
                     }
 
-                    files.sort_by(|a, b| a.0.cmp(&b.0));
-
-                    let mut file_list = String::new();
-                    for (name, size, is_file) in files {
-                        let icon = if is_file { "📄" } else { "📁" };
-                        let size_str = if is_file {
-                            format_bytes(size)
-                        } else {
-                            "-".to_string()
-                        };
-                        // Build absolute path for links
-                        let link_path = if current_path == "/" || current_path.is_empty() {
-                            format!("/{}", name)
-                        } else {
-                            format!("{}/{}", current_path.trim_end_matches('/'), name)
-                        };
-                        file_list.push_str(&format!(
-                            r#"{{ name: '{}', path: '{}', size: '{}', sizeBytes: {}, icon: '{}', isFile: {} }},"#,
-                            name.replace("'", "\\'"), link_path.replace("'", "\\'"), size_str, size, icon, is_file
-                        ));
+                    // Scripts get the same listing as JSON
+                    if server::listing::wants_json(req.headers()) {
+                        let query = server::listing::ListQuery::from_uri(req.uri());
+                        return server::listing::json_response(full_path, req_path.to_string(), safe_root, query).await;
                     }
 
-                    let html = format!(r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>YEET // {}</title>
-    <link href="https://fonts.googleapis.com/css2?family=Roboto+Mono:wght@400;700&display=swap" rel="stylesheet">
-    <script defer src="https://cdn.jsdelivr.net/npm/alpinejs@3.x.x/dist/cdn.min.js"></script>
-    <style>
-        * {{ margin: 0; padding: 0; box-sizing: border-box; }}
-        body {{
-            background: #0a0e27;
-            color: #00ff9f;
-            font-family: 'Roboto Mono', monospace;
-            padding: 2rem;
-            min-height: 100vh;
-        }}
-        .container {{ max-width: 1200px; margin: 0 auto; }}
-        .header {{
-            border: 2px solid #00ff9f;
-            padding: 1.5rem;
-            margin-bottom: 2rem;
-            background: rgba(0, 255, 159, 0.05);
-        }}
-        .logo {{
-            font-size: 2rem;
-            font-weight: bold;
-            color: #00d4ff;
-            text-shadow: 0 0 10px #00d4ff;
-            margin-bottom: 0.5rem;
-        }}
-        .subtitle {{
-            color: #ff00ff;
-            font-size: 0.9rem;
-        }}
-        .controls {{
-            display: flex;
-            gap: 1rem;
-            margin-bottom: 1rem;
-            flex-wrap: wrap;
-        }}
-        input, select {{
-            background: #1a1f3a;
-            border: 1px solid #00ff9f;
-            color: #00ff9f;
-            padding: 0.5rem 1rem;
-            font-family: 'Roboto Mono', monospace;
-            font-size: 0.9rem;
-        }}
-        input:focus, select:focus {{
-            outline: none;
-            box-shadow: 0 0 10px rgba(0, 255, 159, 0.5);
-        }}
-        table {{
-            width: 100%;
-            border-collapse: collapse;
-            border: 2px solid #00ff9f;
-        }}
-        th {{
-            background: rgba(0, 255, 159, 0.1);
-            padding: 1rem;
-            text-align: left;
-            border-bottom: 2px solid #00ff9f;
-            cursor: pointer;
-            user-select: none;
-            color: #00d4ff;
-        }}
-        th:hover {{
-            background: rgba(0, 255, 159, 0.2);
-        }}
-        td {{
-            padding: 0.75rem 1rem;
-            border-bottom: 1px solid rgba(0, 255, 159, 0.2);
-        }}
-        tr:hover {{
-            background: rgba(0, 255, 159, 0.05);
-        }}
-        a {{
-            color: #00ff9f;
-            text-decoration: none;
-            display: flex;
-            align-items: center;
-            gap: 0.5rem;
-        }}
-        a:hover {{
-            color: #00d4ff;
-            text-shadow: 0 0 5px #00d4ff;
-        }}
-        .icon {{ font-size: 1.2rem; }}
-        .stats {{
-            margin-top: 1rem;
-            padding: 1rem;
-            border: 1px solid rgba(255, 0, 255, 0.3);
-            background: rgba(255, 0, 255, 0.05);
-            color: #ff00ff;
-            font-size: 0.85rem;
-        }}
-        .sort-indicator {{
-            font-size: 0.7rem;
-            margin-left: 0.5rem;
-            color: #ff00ff;
-        }}
-    </style>
-</head>
-<body x-data="fileManager()">
-    <div class="container">
-        <div class="header">
-            <div class="logo">█ YEET.SH █</div>
-            <div class="subtitle">// {}</div>
-        </div>
-
-        <div class="controls">
-            <input
-                type="text"
-                x-model="search"
-                placeholder="⚡ SEARCH FILES..."
-                style="flex: 1; min-width: 200px;">
-            <select x-model="filter">
-                <option value="all">ALL FILES</option>
-                <option value="files">FILES ONLY</option>
-                <option value="dirs">DIRS ONLY</option>
-            </select>
-        </div>
-
-        <table>
-            <thead>
-                <tr>
-                    <th @click="sortBy('name')" style="width: 50%">
-                        NAME
-                        <span class="sort-indicator" x-show="sortKey === 'name'" x-text="sortAsc ? '▲' : '▼'"></span>
-                    </th>
-                    <th @click="sortBy('size')" style="width: 30%">
-                        SIZE
-                        <span class="sort-indicator" x-show="sortKey === 'size'" x-text="sortAsc ? '▲' : '▼'"></span>
-                    </th>
-                    <th style="width: 20%">TYPE</th>
-                </tr>
-            </thead>
-            <tbody>
-                <template x-for="file in filteredFiles" :key="file.name">
-                    <tr>
-                        <td>
-                            <a :href="file.path">
-                                <span class="icon" x-text="file.icon"></span>
-                                <span x-text="file.name"></span>
-                            </a>
-                        </td>
-                        <td x-text="file.size"></td>
-                        <td x-text="file.isFile ? 'FILE' : 'DIR'"></td>
-                    </tr>
-                </template>
-            </tbody>
-        </table>
-
-        <div class="stats">
-            <span x-text="stats"></span>
-        </div>
-    </div>
-
-    <script>
-        function fileManager() {{
-            return {{
-                files: [{}],
-                search: '',
-                filter: 'all',
-                sortKey: 'name',
-                sortAsc: true,
-
-                sortBy(key) {{
-                    if (this.sortKey === key) {{
-                        this.sortAsc = !this.sortAsc;
-                    }} else {{
-                        this.sortKey = key;
-                        this.sortAsc = true;
-                    }}
-                }},
-
-                get filteredFiles() {{
-                    let filtered = this.files.filter(f => {{
-                        if (this.search && !f.name.toLowerCase().includes(this.search.toLowerCase())) return false;
-                        if (this.filter === 'files' && !f.isFile) return false;
-                        if (this.filter === 'dirs' && f.isFile) return false;
-                        return true;
-                    }});
-
-                    filtered.sort((a, b) => {{
-                        let aVal = this.sortKey === 'size' ? a.sizeBytes : a.name.toLowerCase();
-                        let bVal = this.sortKey === 'size' ? b.sizeBytes : b.name.toLowerCase();
-                        return this.sortAsc ?
-                            (aVal < bVal ? -1 : 1) :
-                            (aVal > bVal ? -1 : 1);
-                    }});
-
-                    return filtered;
-                }},
-
-                get stats() {{
-                    let total = this.filteredFiles.length;
-                    let files = this.filteredFiles.filter(f => f.isFile).length;
-                    let dirs = total - files;
-                    return `▸ SHOWING ${{total}} ITEMS // ${{files}} FILES // ${{dirs}} DIRECTORIES`;
-                }}
-            }}
-        }}
-    </script>
-</body>
-</html>"#, dir_name, dir_name, file_list);
-
-                    Response::builder()
-                        .status(StatusCode::OK)
-                        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
-                        .body(Body::from(html))
-                        .unwrap()
+                    server::listing::response(&full_path, req_path, &safe_root, req.headers())
                 }
             };
 
             Router::new()
-                .route("/api/stats", axum::routing::get(api_stats_handler))
-                .route("/api/logs", axum::routing::get(api_logs_handler))
-                .route("/admin", axum::routing::get(admin_handler))
+                .merge(admin_routes(false))
+                .merge(server::assets::router())
+                .merge(dav_routes)
+                .merge(checksum_routes)
+                .merge(api_routes)
                 .fallback(index_handler)
+                .layer(server::compression::layer())
+                .layer(axum::middleware::map_response(server::compression::adjust_for_encoding))
+                .layer(axum::middleware::from_fn_with_state(expiry.clone(), server::expiry::enforce))
+                .layer(axum::middleware::from_fn_with_state(stats.clone(), server::stats::track))
+                .with_state(stats.clone())
+        } else if follow {
+            // Growing file: live view at `/`, snapshot and streams at `/<name>`
+            Router::new()
+                .merge(admin_routes(false))
+                .merge(server::assets::router())
+                .merge(server::follow::router(file_path.clone(), shutdown.clone()))
+                .layer(server::compression::layer())
+                .layer(axum::middleware::map_response(server::compression::adjust_for_encoding))
+                .layer(axum::middleware::from_fn_with_state(expiry.clone(), server::expiry::enforce))
+                .layer(axum::middleware::from_fn_with_state(stats.clone(), server::stats::track))
+                .with_state(stats.clone())
         } else {
-            // Serve single file
+            // Serve single file, publishing its checksums to the TUI once hashed
+            let hashed_state = state.clone();
+            let hashed_path = file_path.clone();
+            tokio::spawn(async move {
+                if let Some(checksums) = server::checksum::ready(&hashed_path).await {
+                    hashed_state.update(|s| {
+                        s.sha256 = Some(checksums.sha256);
+                        s.blake3 = checksums.blake3;
+                    });
+                }
+            });
             let filename = file_path.file_name().unwrap().to_string_lossy().to_string();
-            let serve_path = format!("/{}", filename);
+            let serve_path = format!("/{}", server::paths::encode_segment(&filename));
             let file_path_clone = file_path.clone();
 
             // Handler that serves the file
-            let serve_file = move || {
+            let serve_file = move |uri: axum::http::Uri, headers: axum::http::HeaderMap| {
                 let path = file_path_clone.clone();
-                async move {
-                    match tokio::fs::read(&path).await {
-                        Ok(contents) => {
-                            Response::builder()
-                                .status(StatusCode::OK)
-                                .header(header::CONTENT_TYPE, "application/octet-stream")
-                                .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"",
-                                    path.file_name().unwrap().to_string_lossy()))
-                                .body(Body::from(contents))
-                                .unwrap()
-                        }
-                        Err(_) => {
-                            Response::builder()
-                                .status(StatusCode::NOT_FOUND)
-                                .body(Body::from("File not found"))
-                                .unwrap()
-                        }
-                    }
-                }
+                let inline = server::content_type::wants_inline(uri.query());
+                async move { server::files::serve_file(&path, &headers, inline).await }
             };
 
             Router::new()
-                .route("/api/stats", axum::routing::get(api_stats_handler))
-                .route("/api/logs", axum::routing::get(api_logs_handler))
-                .route("/admin", axum::routing::get(admin_handler))
+                .merge(admin_routes(false))
+                .merge(server::assets::router())
                 .route(&serve_path, axum::routing::get(serve_file.clone()))
                 .route("/", axum::routing::get(serve_file))
+                .layer(server::compression::layer())
+                .layer(axum::middleware::map_response(server::compression::adjust_for_encoding))
+                .layer(axum::middleware::from_fn_with_state(expiry.clone(), server::expiry::enforce))
+                .layer(axum::middleware::from_fn_with_state(stats.clone(), server::stats::track))
+                .with_state(stats.clone())
         };
 
-        let addr = format!("127.0.0.1:{}", port);
+        // Access control wraps the whole router: it may rewrite secret-link paths before routing
+        use tower::Layer;
+        let app = axum::middleware::from_fn_with_state(Arc::new(auth.clone()), server::auth::enforce).layer(app);
+
+        let addr = SocketAddr::new(tunnel.bind_addr(), port);
         let listener = tokio::net::TcpListener::bind(&addr).await.expect("Failed to bind");
 
         // Start server in background
+        let server_shutdown = shutdown.clone();
         let server_handle = tokio::spawn(async move {
-            axum::serve(listener, app.into_make_service()).await.expect("Server failed");
+            axum::serve(listener, axum::ServiceExt::<axum::extract::Request>::into_make_service_with_connect_info::<SocketAddr>(app))
+                .with_graceful_shutdown(server_shutdown.cancelled_owned())
+                .await
+                .expect("Server failed");
         });
 
+        // Shut down once --expire / --idle-timeout / --max-downloads is reached
+        tokio::spawn(server::expiry::watch(expiry, stats, state.clone(), shutdown.clone()));
+
         // Wait a bit for server to start
         tokio::time::sleep(Duration::from_secs(2)).await;
 
-        // Start cloudflared
-        start_cloudflared_daemon(file_path, port, daemon_pid, is_dir).await;
+        // Start the tunnel (returns once it has exited or the daemon is shutting down)
+        run_tunnel(tunnel, state, file_path, at_root, &auth, shutdown.clone()).await;
 
-        // Keep daemon alive
-        server_handle.await.ok();
+        // Give in-flight requests a moment to finish, then exit
+        shutdown.cancel();
+        let _ = tokio::time::timeout(SHUTDOWN_GRACE, server_handle).await;
     });
 }
 
-async fn start_cloudflared_daemon(file_path: PathBuf, port: u16, daemon_pid: u32, is_dir: bool) {
-    use std::io::{BufRead, BufReader};
-
-    let mut tunnel = Command::new("cloudflared")
-        .args(&["tunnel", "--url", &format!("http://localhost:{}", port)])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .expect("Failed to start cloudflared");
-
-    if let Some(stderr) = tunnel.stderr.take() {
-        let reader = BufReader::new(stderr);
-        let mut url_saved = false;
-
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if !url_saved && line.contains("trycloudflare.com") {
-                    use regex::Regex;
-                    let re = Regex::new(r"https://[^\s]+\.trycloudflare\.com").unwrap();
-                    if let Some(mat) = re.find(&line) {
-                        let base_url = mat.as_str();
-
-                        // For directories, use base URL; for files, append filename
-                        let url = if is_dir {
-                            base_url.to_string()
-                        } else {
-                            let filename = file_path.file_name().unwrap().to_string_lossy().to_string();
-                            format!("{}/{}", base_url, filename)
-                        };
-
-                        // Save state
-                        use std::time::{SystemTime, UNIX_EPOCH};
-                        let state = TunnelState {
-                            url,
-                            pid: daemon_pid,
-                            port,
-                            file_path: file_path.to_string_lossy().to_string(),
-                            created_at: SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs(),
-                        };
-                        let _ = state.save();
-                        url_saved = true;
-                        // Continue reading stderr to keep pipe open
-                    }
-                }
-            }
-        }
-    }
-
-    // Keep tunnel alive forever
-    let _ = tunnel.wait();
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-struct TunnelState {
-    url: String,
-    pid: u32,
-    port: u16,
-    file_path: String,
-    created_at: u64, // unix timestamp
-}
-
-impl TunnelState {
-    fn state_file() -> PathBuf {
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        let yeet_dir = PathBuf::from(home).join(".yeet");
-        fs::create_dir_all(&yeet_dir).ok();
-        yeet_dir.join("tunnel.state")
-    }
-
-    fn load() -> Option<Self> {
-        let path = Self::state_file();
-        if path.exists() {
-            let content = fs::read_to_string(path).ok()?;
-            serde_json::from_str(&content).ok()
-        } else {
-            None
-        }
-    }
-
-    fn save(&self) -> Result<()> {
-        let path = Self::state_file();
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(path, content)?;
-        Ok(())
-    }
+async fn run_tunnel(
+    provider: Arc<dyn tunnel::TunnelProvider>,
+    state: state::StateHandle,
+    file_path: PathBuf,
+    at_root: bool,
+    auth: &server::auth::AuthConfig,
+    shutdown: tokio_util::sync::CancellationToken,
+) {
+    let port = state.port();
+    tunnel::supervisor::supervise(provider, port, shutdown, |status| match status {
+        tunnel::supervisor::Status::Url(base_url) => {
+            // Secret links put the token in front of everything else
+            let share_base = match &auth.secret {
+                Some(secret) => format!("{}/{}", base_url, secret),
+                None => base_url.clone(),
+            };
 
-    fn delete() {
-        let path = Self::state_file();
-        let _ = fs::remove_file(path);
-    }
+            // For directories and proxies, use base URL; for files, append filename
+            let url = if at_root {
+                share_base
+            } else {
+                let filename = file_path.file_name().unwrap().to_string_lossy().to_string();
+                format!("{}/{}", share_base, server::paths::encode_segment(&filename))
+            };
 
-    fn is_tunnel_alive(&self) -> bool {
-        // Check if the process is still running
-        #[cfg(unix)]
-        {
-            use std::process::Command as StdCommand;
-            StdCommand::new("kill")
-                .arg("-0")
-                .arg(self.pid.to_string())
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false)
-        }
-        #[cfg(not(unix))]
-        {
-            // Windows fallback - always assume dead
-            false
+            let admin_url = server::auth::admin_url(&base_url, auth);
+            state.update(|s| {
+                s.url = Some(url);
+                s.admin_url = Some(admin_url);
+            });
         }
-    }
-
-    fn age_hours(&self) -> f64 {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        (now - self.created_at) as f64 / 3600.0
-    }
+        tunnel::supervisor::Status::Restarting { restarts, error } => state.update(|s| {
+            s.tunnel_restarts = restarts;
+            s.tunnel_error = Some(error);
+        }),
+        tunnel::supervisor::Status::Failed(reason) => state.update(|s| {
+            s.ended_reason.get_or_insert(reason);
+        }),
+    })
+    .await;
 }
 
 #[derive(Parser)]
 #[command(name = "yeet")]
 #[command(about = "🚀 Yeet files and directories across the internet at warp speed", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// File or directory to yeet
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// File or directory to yeet, or a local server to expose (`:3000`, `http://localhost:3000`)
     file: Option<PathBuf>,
 
-    /// Port for HTTP server (default: 8000)
-    #[arg(short, long, default_value = "8000")]
-    port: u16,
+    /// Port for HTTP server (default: first free port from 8000)
+    #[arg(short, long)]
+    port: Option<u16>,
 
     /// Keep tunnel alive in background (daemon mode)
     #[arg(short, long)]
     daemon: bool,
 
-    /// Show existing tunnel status
-    #[arg(long)]
-    status: bool,
+    /// Show status of all shares, or of one share by id
+    #[arg(long, value_name = "ID", num_args = 0..=1, default_missing_value = "")]
+    status: Option<String>,
 
-    /// Kill existing tunnel daemon
+    /// Kill all running share daemons
     #[arg(long)]
     kill: bool,
+
+    /// How symlinks inside a shared directory are handled
+    #[arg(long, value_enum, default_value = "within-root")]
+    symlinks: server::paths::SymlinkPolicy,
+
+    /// Require this password (HTTP Basic auth, any username) to download
+    #[arg(long)]
+    password: Option<String>,
+
+    /// Put an unguessable token in the shared URL; requests without it get 404
+    #[arg(long)]
+    secret_link: bool,
+
+    /// Credential for /admin and its API (random if not set)
+    #[arg(long)]
+    admin_password: Option<String>,
+
+    /// Shut the share down after this long (e.g. 30m, 2h, 1d)
+    #[arg(long, value_name = "DURATION", value_parser = server::expiry::parse_duration)]
+    expire: Option<Duration>,
+
+    /// Shut the share down after this long without any requests
+    #[arg(long, value_name = "DURATION", value_parser = server::expiry::parse_duration)]
+    idle_timeout: Option<Duration>,
+
+    /// Shut the share down after N downloads (counted when they start)
+    #[arg(long, value_name = "N")]
+    max_downloads: Option<u64>,
+
+    /// Drop-box mode: serve an upload page that saves files into DIR
+    #[arg(long, value_name = "DIR", conflicts_with = "file")]
+    receive: Option<PathBuf>,
+
+    /// Also serve a shared directory over WebDAV at /dav (read-only)
+    #[arg(long)]
+    webdav: bool,
+
+    /// Allow PUT, MKCOL, DELETE, COPY and MOVE over WebDAV (implies --webdav)
+    #[arg(long)]
+    webdav_write: bool,
+
+    /// Derive ETags from the file checksums rather than size and mtime
+    #[arg(long, conflicts_with = "no_checksums")]
+    hash_etags: bool,
+
+    /// Largest accepted upload in --receive mode or WebDAV PUT (e.g. 500M, 2G)
+    #[arg(long, value_name = "SIZE", default_value = "10G", value_parser = server::upload::parse_size)]
+    max_upload_size: u64,
+
+    /// Let uploads replace existing files instead of getting a numbered name
+    #[arg(long)]
+    overwrite: bool,
+
+    /// Download file name when sharing stdin (`yeet -`)
+    #[arg(long, value_name = "NAME")]
+    name: Option<String>,
+
+    /// Spool stdin to disk (up to MAX, default 1G) so anyone can download it,
+    /// instead of streaming it to the first downloader only
+    #[arg(long, value_name = "MAX", num_args = 0..=1, default_missing_value = "1G", value_parser = server::upload::parse_size)]
+    spool: Option<u64>,
+
+    /// Serve a live, auto-scrolling view of a growing file (like `tail -f`)
+    #[arg(long)]
+    follow: bool,
+
+    /// Don't compute SHA-256 checksums of shared files in the background
+    #[arg(long)]
+    no_checksums: bool,
+
+    /// Also compute BLAKE3 checksums (served as /B3SUMS for directories)
+    #[arg(long, conflicts_with = "no_checksums")]
+    blake3: bool,
+
+    /// How the share is exposed
+    #[arg(long, value_enum, default_value = "cloudflare")]
+    tunnel: tunnel::TunnelKind,
+
+    /// Named Cloudflare tunnel to run (--tunnel cloudflare-named)
+    #[arg(long, value_name = "NAME")]
+    tunnel_name: Option<String>,
+
+    /// Public hostname routed to the named tunnel (--tunnel cloudflare-named)
+    #[arg(long)]
+    hostname: Option<String>,
+
+    /// SSH destination for the reverse tunnel, e.g. user@host (--tunnel ssh)
+    #[arg(long, value_name = "USER@HOST")]
+    ssh_target: Option<String>,
+
+    /// Port opened on the SSH host (--tunnel ssh, default 8080)
+    #[arg(long, value_name = "PORT")]
+    ssh_remote_port: Option<u16>,
+
+    /// Public URL the SSH host serves the forwarded port on (--tunnel ssh)
+    #[arg(long, value_name = "URL")]
+    public_url: Option<String>,
+}
+
+#[derive(clap::Subcommand)]
+enum Commands {
+    /// List all shares
+    List,
+    /// Stop a share by id (or unambiguous id prefix)
+    Stop {
+        id: String,
+    },
 }
 
 struct App {
+    share_id: String,
     file_path: PathBuf,
     file_size: u64,
     is_dir: bool,
     port: u16,
     tunnel_url: Option<String>,
+    admin_url: Option<String>,
+    limits: Option<String>,
+    tunnel_health: Option<String>,
+    receiving: bool,
+    proxying: bool,
+    stdin: Option<state::StdinProgress>,
+    following: bool,
+    sha256: Option<String>,
+    blake3: Option<String>,
+    received: Vec<state::ReceivedFile>,
+    received_total: u64,
+    ended_reason: Option<String>,
     frame_count: u32,
     daemon_pid: Option<u32>,
     daemon_age: Option<f64>,
@@ -920,47 +619,77 @@ struct App {
 }
 
 impl App {
-    fn new(file_path: PathBuf, port: u16) -> Result<Self> {
-        let metadata = std::fs::metadata(&file_path)?;
-        let is_dir = metadata.is_dir();
-        let file_size = if is_dir { 0 } else { metadata.len() };
-
+    fn new(share_id: String, file_path: PathBuf, port: u16) -> Result<Self> {
         // Load initial state from daemon
-        let (tunnel_url, daemon_pid, daemon_age) = if let Some(state) = TunnelState::load() {
-            let age = state.age_hours();
-            (Some(state.url.clone()), Some(state.pid), Some(age))
+        let state = TunnelState::load(&share_id);
+        let proxying = state.as_ref().is_some_and(|s| s.proxy);
+        let stdin = state.as_ref().and_then(|s| s.stdin.clone());
+
+        // A proxied server or piped input has no file on disk to describe
+        let (is_dir, file_size) = if proxying || stdin.is_some() {
+            (false, 0)
         } else {
-            (None, None, None)
+            let metadata = std::fs::metadata(&file_path)?;
+            (metadata.is_dir(), if metadata.is_dir() { 0 } else { metadata.len() })
         };
 
         Ok(Self {
+            share_id,
             file_path,
             file_size,
             is_dir,
             port,
-            tunnel_url,
+            tunnel_url: state.as_ref().and_then(|s| s.url.clone()),
+            admin_url: state.as_ref().and_then(|s| s.admin_url.clone()),
+            limits: state.as_ref().and_then(|s| s.limits_summary()),
+            tunnel_health: state.as_ref().and_then(|s| s.tunnel_summary()),
+            receiving: state.as_ref().is_some_and(|s| s.receive),
+            proxying,
+            stdin,
+            following: state.as_ref().is_some_and(|s| s.follow),
+            sha256: state.as_ref().and_then(|s| s.sha256.clone()),
+            blake3: state.as_ref().and_then(|s| s.blake3.clone()),
+            received: state.as_ref().map(|s| s.received.clone()).unwrap_or_default(),
+            received_total: state.as_ref().map_or(0, |s| s.received_total),
+            ended_reason: state.as_ref().and_then(|s| s.ended_reason.clone()),
             frame_count: 0,
-            daemon_pid,
-            daemon_age,
+            daemon_pid: state.as_ref().map(|s| s.pid),
+            daemon_age: state.as_ref().map(|s| s.age_hours()),
             yeet_tui: tui::YeetTui::new(),  // 🎮 Initialize retro TUI
         })
     }
 
     fn refresh_state(&mut self) {
         // Reload state from daemon
-        if let Some(state) = TunnelState::load() {
+        if let Some(state) = TunnelState::load(&self.share_id) {
             let age = state.age_hours();
-            self.tunnel_url = Some(state.url.clone());
+            self.tunnel_url = state.url.clone();
+            self.admin_url = state.admin_url.clone();
+            self.limits = state.limits_summary();
+            self.tunnel_health = state.tunnel_summary();
+            self.receiving = state.receive;
+            self.stdin = state.stdin.clone();
+            self.sha256 = state.sha256.clone();
+            self.blake3 = state.blake3.clone();
+            self.received = state.received.clone();
+            self.received_total = state.received_total;
+            self.ended_reason = state.ended_reason.clone();
             self.daemon_pid = Some(state.pid);
             self.daemon_age = Some(age);
         }
+        // A followed file keeps growing
+        if self.following {
+            if let Ok(metadata) = std::fs::metadata(&self.file_path) {
+                self.file_size = metadata.len();
+            }
+        }
     }
 
     fn tick(&mut self) {
         self.frame_count = self.frame_count.wrapping_add(1);
         self.yeet_tui.tick();  // 🎮 Tick retro animations
         // Refresh state from daemon every few ticks
-        if self.frame_count % 30 == 0 {
+        if self.frame_count.is_multiple_of(30) {
             self.refresh_state();
         }
     }
@@ -982,34 +711,46 @@ impl App {
 fn ui(f: &mut Frame, app: &App) {
     let size = f.area();
 
-    // Create layout
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(6),  // Logo
-            Constraint::Length(5),  // Info
-            Constraint::Min(6),     // URL panel
-            Constraint::Length(1),  // Footer
-        ])
-        .split(size);
-
-    // 🎮 Render COLORFUL retro YEET logo
-    app.yeet_tui.render_logo(f, chunks[0]);
-
     // Info box
     let mut info_lines = vec![
         Line::from(vec![
-            Span::styled(if app.is_dir { "DIR: " } else { "FILE: " },
+            Span::styled(if app.receiving { "RECEIVING INTO: " } else if app.proxying { "PROXYING: " } else if app.is_dir { "DIR: " } else if app.following { "FOLLOWING: " } else { "FILE: " },
                 Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
-            Span::raw(app.file_path.file_name().unwrap().to_string_lossy()),
+            if app.proxying {
+                Span::raw(app.file_path.to_string_lossy())
+            } else {
+                Span::raw(app.file_path.file_name().unwrap().to_string_lossy())
+            },
         ]),
     ];
 
-    if !app.is_dir {
+    if let Some(stdin) = &app.stdin {
+        let status = match (stdin.finished, stdin.spooled) {
+            (true, _) => "all read",
+            (false, true) => "spooling...",
+            (false, false) => "streaming to first downloader",
+        };
+        info_lines.push(Line::from(vec![
+            Span::styled("STDIN: ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{} ({})", format_bytes(stdin.bytes), status)),
+        ]));
+    } else if !app.is_dir && !app.proxying {
         info_lines.push(Line::from(vec![
             Span::styled("SIZE: ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
             Span::raw(app.format_size()),
         ]));
+        if let Some(sha256) = &app.sha256 {
+            info_lines.push(Line::from(vec![
+                Span::styled("SHA256: ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+                Span::raw(sha256.as_str()),
+            ]));
+        }
+        if let Some(blake3) = &app.blake3 {
+            info_lines.push(Line::from(vec![
+                Span::styled("BLAKE3: ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+                Span::raw(blake3.as_str()),
+            ]));
+        }
     }
 
     info_lines.push(Line::from(vec![
@@ -1017,6 +758,11 @@ fn ui(f: &mut Frame, app: &App) {
         Span::raw(format!("{}", app.port)),
     ]));
 
+    info_lines.push(Line::from(vec![
+        Span::styled("SHARE ID: ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+        Span::styled(app.share_id.clone(), Style::default().fg(Color::Green)),
+    ]));
+
     if let Some(pid) = app.daemon_pid {
         info_lines.push(Line::from(vec![
             Span::styled("DAEMON PID: ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
@@ -1024,6 +770,21 @@ fn ui(f: &mut Frame, app: &App) {
         ]));
     }
 
+    // Create layout; the info box grows with its lines (checksums, stdin progress)
+    let info_height = (info_lines.len() as u16 + 2).min(10);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(6),  // Logo
+            Constraint::Length(info_height),  // Info
+            Constraint::Min(6),     // URL panel
+            Constraint::Length(1),  // Footer
+        ])
+        .split(size);
+
+    // 🎮 Render COLORFUL retro YEET logo
+    app.yeet_tui.render_logo(f, chunks[0]);
+
     let info = Paragraph::new(info_lines)
         .block(
             Block::default()
@@ -1046,6 +807,14 @@ fn ui(f: &mut Frame, app: &App) {
             Line::from(""),
         ];
 
+        if let Some(admin_url) = &app.admin_url {
+            url_lines.push(Line::from(vec![
+                Span::styled("ADMIN: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::styled(admin_url, Style::default().fg(Color::DarkGray)),
+            ]));
+            url_lines.push(Line::from(""));
+        }
+
         if let Some(age) = app.daemon_age {
             url_lines.push(Line::from(vec![
                 Span::styled("UPTIME: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
@@ -1054,6 +823,42 @@ fn ui(f: &mut Frame, app: &App) {
             url_lines.push(Line::from(""));
         }
 
+        if let Some(health) = &app.tunnel_health {
+            url_lines.push(Line::from(vec![
+                Span::styled("TUNNEL: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::raw(health.clone()),
+            ]));
+            url_lines.push(Line::from(""));
+        }
+
+        if app.receiving {
+            url_lines.push(Line::from(vec![
+                Span::styled("INCOMING: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::raw(format!("{} file(s) received", app.received_total)),
+            ]));
+            for file in app.received.iter().rev().take(3) {
+                url_lines.push(Line::from(vec![
+                    Span::styled("  ⬇ ", Style::default().fg(Color::Green)),
+                    Span::raw(format!("{} ({})", file.name, format_bytes(file.size))),
+                ]));
+            }
+            url_lines.push(Line::from(""));
+        }
+
+        if let Some(reason) = &app.ended_reason {
+            url_lines.push(Line::from(vec![
+                Span::styled("ENDED: ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(reason.clone()),
+            ]));
+            url_lines.push(Line::from(""));
+        } else if let Some(limits) = &app.limits {
+            url_lines.push(Line::from(vec![
+                Span::styled("LIMITS: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::raw(limits.clone()),
+            ]));
+            url_lines.push(Line::from(""));
+        }
+
         url_lines.push(Line::from(vec![
             Span::styled("░▒▓ ", Style::default().fg(Color::Magenta)),
             Span::raw("Daemon running in background"),
@@ -1064,7 +869,7 @@ fn ui(f: &mut Frame, app: &App) {
         ]));
         url_lines.push(Line::from(vec![
             Span::styled("░▒▓ ", Style::default().fg(Color::Magenta)),
-            Span::raw("Use 'yeet --kill' to stop daemon"),
+            Span::raw(format!("Use 'yeet stop {}' to stop daemon", app.share_id)),
         ]));
 
         let url_panel = Paragraph::new(url_lines)
@@ -1113,139 +918,290 @@ fn run_app<B: ratatui::backend::Backend>(
     }
 }
 
+/// Print the state of one share (used by --status and `yeet list`)
+fn print_share(state: &TunnelState) {
+    let url = state.url.as_deref().unwrap_or("(waiting for tunnel)");
+    if state.is_tunnel_alive() {
+        println!("✓ [{}] ALIVE", state.id);
+        println!("  URL:     {}", url);
+        if state.proxy {
+            println!("  Proxy:   {}", state.file_path);
+        } else if state.follow {
+            println!("  Follow:  {}", state.file_path);
+        } else {
+            println!("  File:    {}", state.file_path);
+        }
+        if let Some(sha256) = &state.sha256 {
+            println!("  SHA256:  {}", sha256);
+        }
+        println!("  Port:    {}", state.port);
+        match state.tunnel_summary() {
+            Some(health) => println!("  Tunnel:  {} ({})", state.tunnel, health),
+            None => println!("  Tunnel:  {}", state.tunnel),
+        }
+        println!("  PID:     {}", state.pid);
+        println!("  Age:     {:.1} hours", state.age_hours());
+        if let Some(admin_url) = &state.admin_url {
+            println!("  Admin:   {}", admin_url);
+        }
+        if let Some(limits) = state.limits_summary() {
+            println!("  Limits:  {}", limits);
+        }
+        if state.receive {
+            println!("  Received: {} file(s)", state.received_total);
+            for file in state.received.iter().rev().take(5) {
+                println!("    ⬇ {} ({})", file.name, format_bytes(file.size));
+            }
+        }
+    } else {
+        match &state.ended_reason {
+            Some(reason) => println!("✗ [{}] ENDED ({})", state.id, reason),
+            None => println!("✗ [{}] DEAD (process not running)", state.id),
+        }
+        println!("  Last URL: {}", url);
+        println!("  File:     {}", state.file_path);
+        state.delete();
+    }
+}
+
 fn main() -> Result<()> {
     let _ = color_eyre::install();
 
     let cli = Cli::parse();
 
-    // Handle --status flag
-    if cli.status {
-        if let Some(state) = TunnelState::load() {
+    match cli.command {
+        // `yeet list` - every registered share
+        Some(Commands::List) => {
+            let shares = TunnelState::all();
+            if shares.is_empty() {
+                println!("No shares running");
+            }
+            for state in &shares {
+                print_share(state);
+            }
+            return Ok(());
+        }
+        // `yeet stop <id>` - stop one share
+        Some(Commands::Stop { id }) => {
+            let Some(state) = TunnelState::find(&id) else {
+                anyhow::bail!("No share with id '{}' (see 'yeet list')", id);
+            };
             if state.is_tunnel_alive() {
-                println!("✓ Tunnel is ALIVE");
-                println!("  URL:     {}", state.url);
-                println!("  File:    {}", state.file_path);
-                println!("  Port:    {}", state.port);
-                println!("  PID:     {}", state.pid);
-                println!("  Age:     {:.1} hours", state.age_hours());
+                state.stop()?;
+                println!("✓ Stopped share {} (PID {})", state.id, state.pid);
             } else {
-                println!("✗ Tunnel is DEAD (process not running)");
-                println!("  Last URL: {}", state.url);
-                TunnelState::delete();
+                println!("✗ Share {} already dead", state.id);
+            }
+            state.delete();
+            return Ok(());
+        }
+        None => {}
+    }
+
+    // Handle --status flag
+    if let Some(id) = cli.status.as_deref() {
+        if id.is_empty() {
+            let shares = TunnelState::all();
+            if shares.is_empty() {
+                println!("No tunnel state found");
+            }
+            for state in &shares {
+                print_share(state);
             }
+        } else if let Some(state) = TunnelState::find(id) {
+            print_share(&state);
         } else {
-            println!("No tunnel state found");
+            println!("No share with id '{}'", id);
         }
         return Ok(());
     }
 
     // Handle --kill flag
     if cli.kill {
-        if let Some(state) = TunnelState::load() {
+        let shares = TunnelState::all();
+        if shares.is_empty() {
+            println!("No tunnel to kill");
+        }
+        for state in shares {
             if state.is_tunnel_alive() {
-                #[cfg(unix)]
-                {
-                    // Kill the entire process group to stop daemon + cloudflared
-                    std::process::Command::new("kill")
-                        .arg("-9")
-                        .arg(format!("-{}", state.pid))
-                        .output()?;
-                    println!("✓ Killed tunnel daemon and children (PID {})", state.pid);
-                }
-                #[cfg(not(unix))]
-                {
-                    println!("✗ Kill not supported on this platform");
+                match state.stop() {
+                    Ok(()) => println!("✓ Killed share {} and children (PID {})", state.id, state.pid),
+                    Err(e) => println!("✗ Failed to kill share {}: {}", state.id, e),
                 }
             } else {
-                println!("✗ Tunnel already dead");
+                println!("✗ Share {} already dead", state.id);
             }
-            TunnelState::delete();
-        } else {
-            println!("No tunnel to kill");
+            state.delete();
         }
         return Ok(());
     }
 
     // Require file/directory for normal operation
-    let file = cli.file.ok_or_else(|| anyhow::anyhow!("File or directory path required (or use --status/--kill)"))?;
-
-    // Validate path exists
-    if !file.exists() {
-        anyhow::bail!("Path not found: {}", file.display());
+    let receiving = cli.receive.is_some();
+    // `:3000` or `http://localhost:3000` exposes a local server instead of a path
+    let proxy = match (&cli.receive, &cli.file) {
+        (None, Some(file)) => file.to_str().map(server::proxy::Target::parse).transpose()?.flatten(),
+        _ => None,
+    };
+    let proxying = proxy.is_some();
+    // `-` shares whatever is piped in (`pg_dump | yeet - --name db.sql`)
+    let stdin = if !receiving && cli.file.as_deref() == Some(std::path::Path::new("-")) {
+        if io::stdin().is_terminal() {
+            anyhow::bail!("Nothing piped into stdin (try 'cmd | yeet -')");
+        }
+        let name = cli.name.as_deref().unwrap_or("stdin");
+        let name = server::upload::sanitize_filename(name)
+            .ok_or_else(|| anyhow::anyhow!("Invalid --name: {}", name))?;
+        Some(server::stdin::StdinConfig { name, spool_limit: cli.spool })
+    } else {
+        if cli.name.is_some() || cli.spool.is_some() {
+            anyhow::bail!("--name and --spool only apply when sharing stdin ('yeet -')");
+        }
+        None
+    };
+    let settings = share_settings(&cli);
+    let file = cli.receive.clone().or(cli.file).ok_or_else(|| anyhow::anyhow!("File or directory path required (or use list/stop/--status/--kill)"))?;
+
+    // A drop box may point at a directory that doesn't exist yet
+    if receiving {
+        std::fs::create_dir_all(&file)?;
+        if !file.is_dir() {
+            anyhow::bail!("--receive needs a directory: {}", file.display());
+        }
     }
 
-    // Check for existing tunnel
-    let daemon_exists = if let Some(state) = TunnelState::load() {
-        if state.is_tunnel_alive() && state.port == cli.port {
-            // Check if the file path matches
-            let requested_path = file.canonicalize().unwrap_or(file.clone());
-            let daemon_path = PathBuf::from(&state.file_path);
-
-            if requested_path == daemon_path {
-                // Same file/dir - reuse tunnel
-                println!("Found existing tunnel (age: {:.1}h)", state.age_hours());
-                println!("URL: {}", state.url);
-                println!("\nReusing tunnel... Starting TUI...");
-                println!("Press 'q' to exit TUI (tunnel stays alive)");
-                println!("Use 'yeet --kill' to stop the daemon");
-                thread::sleep(Duration::from_secs(2));
-                true
-            } else {
-                // Different file/dir - restart daemon
-                println!("🔄 Different file requested - restarting daemon");
-                println!("   Old: {}", state.file_path);
-                println!("   New: {}", requested_path.display());
-
-                // Kill old daemon
-                if let Err(e) = nix::sys::signal::kill(
-                    nix::unistd::Pid::from_raw(state.pid as i32),
-                    nix::sys::signal::Signal::SIGTERM
-                ) {
-                    eprintln!("⚠️  Failed to kill old daemon: {}", e);
-                }
-
-                TunnelState::delete();
-                thread::sleep(Duration::from_millis(500)); // Give time for cleanup
-                false
+    let file = match (&proxy, &stdin) {
+        (Some(target), _) => PathBuf::from(target.to_string()),
+        (_, Some(stdin)) => PathBuf::from(&stdin.name),
+        (None, None) => {
+            // Validate path exists
+            if !file.exists() {
+                anyhow::bail!("Path not found: {}", file.display());
             }
-        } else {
-            TunnelState::delete();
-            false
+            file.canonicalize()?
         }
-    } else {
-        false
     };
+    if cli.follow && (receiving || proxying || stdin.is_some() || !file.is_file()) {
+        anyhow::bail!("--follow needs a single file");
+    }
+    if (cli.webdav || cli.webdav_write) && (receiving || proxying || stdin.is_some() || !file.is_dir()) {
+        anyhow::bail!("--webdav needs a shared directory");
+    }
 
-    // Spawn daemon if needed
-    if !daemon_exists {
-        println!("🚀 Spawning daemon...");
-        let daemon_pid = spawn_daemon(file.clone(), cli.port)?;
+    let provider = tunnel::build(&tunnel::TunnelOptions {
+        kind: cli.tunnel,
+        tunnel_name: cli.tunnel_name.clone(),
+        hostname: cli.hostname.clone(),
+        ssh_target: cli.ssh_target.clone(),
+        ssh_remote_port: cli.ssh_remote_port,
+        public_url: cli.public_url.clone(),
+    })?;
+
+    // Reuse a live share of the same path and tunnel (on the requested port, if any)
+    let (existing, differing): (Vec<_>, Vec<_>) = TunnelState::all()
+        .into_iter()
+        .filter(|state| {
+            // Piped input can't be shared twice
+            stdin.is_none()
+                && state.is_tunnel_alive()
+                && state.url.is_some()
+                && state.tunnel == provider.name()
+                && state.receive == receiving
+                && state.proxy == proxying
+                && state.follow == cli.follow
+                && std::path::Path::new(&state.file_path) == file
+                && cli.port.is_none_or(|port| port == state.port)
+        })
+        // Never hand back a share with different protection, limits or features
+        .partition(|state| state.settings.as_deref() == Some(settings.as_str()));
+    if let (None, Some(other)) = (existing.first(), differing.first()) {
+        println!("Share {} of this path uses different options, starting a new one", other.id);
+    }
+    let existing = existing.into_iter().next();
+
+    let (share_id, port) = if let Some(state) = existing {
+        println!("Found existing share {} (age: {:.1}h)", state.id, state.age_hours());
+        println!("URL: {}", state.url.as_deref().unwrap_or_default());
+        println!("\nReusing tunnel... Starting TUI...");
+        println!("Press 'q' to exit TUI (tunnel stays alive)");
+        println!("Use 'yeet stop {}' to stop the daemon", state.id);
+        thread::sleep(Duration::from_secs(2));
+        (state.id, state.port)
+    } else {
+        let share_id = state::new_share_id();
+        let port = state::pick_port(cli.port, provider.bind_addr())?;
+
+        println!("🚀 Spawning daemon for share {} on port {}...", share_id, port);
+        let daemon_pid = spawn_daemon(DaemonConfig {
+            share_id: share_id.clone(),
+            file_path: file.clone(),
+            port,
+            symlinks: cli.symlinks,
+            auth: server::auth::AuthConfig {
+                password: cli.password.clone(),
+                secret: cli.secret_link.then(server::auth::random_token),
+                admin_token: cli.admin_password.clone().unwrap_or_else(server::auth::random_token),
+                proxied: proxying,
+            },
+            expiry: server::expiry::ExpiryPolicy {
+                expire: cli.expire,
+                idle_timeout: cli.idle_timeout,
+                max_downloads: cli.max_downloads,
+            },
+            tunnel: provider.clone(),
+            receive: receiving.then(|| server::upload::UploadConfig {
+                dir: file.clone(),
+                max_size: cli.max_upload_size,
+                overwrite: cli.overwrite,
+            }),
+            webdav: (cli.webdav || cli.webdav_write).then_some(server::webdav::WebDavConfig {
+                writable: cli.webdav_write,
+                max_size: cli.max_upload_size,
+            }),
+            hash_etags: cli.hash_etags,
+            proxy: proxy.clone(),
+            stdin: stdin.clone(),
+            follow: cli.follow,
+            checksums: (!cli.no_checksums).then_some(cli.blake3),
+            settings,
+        })?;
         println!("✓ Daemon started (PID: {})", daemon_pid);
         println!("⏳ Waiting for tunnel URL...");
 
-        // Wait for state file to be created (max 30 seconds)
+        // Wait for the daemon to publish its URL (max 30 seconds)
         let start = Instant::now();
         println!("   (Press Ctrl+C to cancel)");
+        let mut ready = false;
         while start.elapsed() < Duration::from_secs(30) {
-            if let Some(state) = TunnelState::load() {
+            let state = TunnelState::load(&share_id);
+            if let Some(reason) = state.as_ref().and_then(|s| s.ended_reason.as_ref()) {
+                eprintln!("\n❌ Tunnel failed: {}", reason);
+                break;
+            }
+            if let Some(state) = state.filter(|s| s.url.is_some()) {
                 println!("✓ Tunnel ready!");
-                println!("  URL: {}", state.url);
+                println!("  URL:   {}", state.url.as_deref().unwrap_or_default());
+                if let Some(admin_url) = &state.admin_url {
+                    println!("  Admin: {}", admin_url);
+                }
+                ready = true;
                 break;
             }
             thread::sleep(Duration::from_millis(500));
         }
 
-        if TunnelState::load().is_none() {
-            eprintln!("\n❌ Daemon failed to create tunnel within 30 seconds");
+        if !ready {
+            eprintln!("\n❌ Daemon failed to create a tunnel");
             eprintln!("   Possible issues:");
-            eprintln!("   - cloudflared not properly installed");
+            eprintln!("   - {} not properly installed", provider.name());
             eprintln!("   - Network restrictions (Docker containers need --network=host)");
-            eprintln!("   - Port {} already in use", cli.port);
-            eprintln!("\n   Try: yeet --kill   (to stop daemon)");
+            eprintln!("   - Port {} already in use", port);
+            eprintln!("\n   Try: yeet stop {}   (to stop daemon)", share_id);
             eprintln!("   Then: yeet <file> --port <different-port>");
             anyhow::bail!("Tunnel creation failed");
         }
-    }
+        (share_id, port)
+    };
 
     // Setup terminal
     enable_raw_mode()?;
@@ -1255,7 +1211,7 @@ fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run (TUI only, daemon runs independently)
-    let app = App::new(file, cli.port)?;
+    let app = App::new(share_id, file, port)?;
     let res = run_app(&mut terminal, app);
 
     // Restore terminal
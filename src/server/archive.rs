@@ -0,0 +1,324 @@
+// 🗜️ Stream a directory subtree as a zip or tar.gz without staging it on disk
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, StatusCode},
+    response::Response,
+};
+use chrono::{DateTime, Datelike, Local, Timelike};
+use flate2::{write::GzEncoder, Compression};
+use futures_util::stream;
+use tokio::sync::mpsc;
+use zip::{write::SimpleFileOptions, write::StreamWriter, CompressionMethod, ZipWriter};
+
+use super::{content_type::content_disposition, paths::SafeRoot};
+
+/// Size of the buffer between the archive encoder and the response body
+const CHUNK_SIZE: usize = 256 * 1024;
+/// Chunks allowed in flight before the encoder waits for the client
+const CHANNEL_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Pick the format out of a `download=zip|tar.gz` query string
+    pub fn from_query(query: Option<&str>) -> Option<Self> {
+        query?
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("download="))
+            .and_then(|value| match value {
+                "zip" => Some(Self::Zip),
+                "tar.gz" | "tgz" => Some(Self::TarGz),
+                _ => None,
+            })
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::TarGz => "tar.gz",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Zip => "application/zip",
+            Self::TarGz => "application/gzip",
+        }
+    }
+}
+
+/// Build a streaming archive response for `dir`. The encoder runs on a
+/// blocking thread and hands chunks to the body through a bounded channel,
+/// so a slow client applies back-pressure instead of buffering in memory.
+pub fn archive_response(dir: PathBuf, root: SafeRoot, format: ArchiveFormat) -> Response {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "yeet".to_string());
+    let filename = format!("{}.{}", name, format.extension());
+
+    let (tx, rx) = mpsc::channel::<io::Result<Bytes>>(CHANNEL_DEPTH);
+    tokio::task::spawn_blocking(move || {
+        let writer = BufWriter::with_capacity(CHUNK_SIZE, ChannelWriter { tx: tx.clone() });
+        let result = match format {
+            ArchiveFormat::Zip => write_zip(writer, &dir, &name, &root),
+            ArchiveFormat::TarGz => write_tar_gz(writer, &dir, &name, &root),
+        };
+        if let Err(e) = result {
+            // Surface the failure so the response is aborted, not silently truncated
+            let _ = tx.blocking_send(Err(e));
+        }
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|chunk| (chunk, rx)) });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(header::CONTENT_DISPOSITION, content_disposition(false, &filename))
+        .body(Body::from_stream(body))
+        .unwrap()
+}
+
+/// `Write` adapter feeding the response body channel
+struct ChannelWriter {
+    tx: mpsc::Sender<io::Result<Bytes>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Destination for entries discovered while walking the tree
+pub(super) trait ArchiveSink {
+    fn add_dir(&mut self, name: &str, path: &Path, metadata: &fs::Metadata) -> io::Result<()>;
+    fn add_file(&mut self, name: &str, path: &Path, metadata: &fs::Metadata) -> io::Result<()>;
+}
+
+fn write_tar_gz<W: Write>(writer: W, dir: &Path, name: &str, root: &SafeRoot) -> io::Result<()> {
+    let mut sink = TarSink(tar::Builder::new(GzEncoder::new(writer, Compression::fast())));
+    walk(dir, name, root, &mut HashSet::new(), &mut sink)?;
+    sink.0.into_inner()?.finish()?.flush()
+}
+
+fn write_zip<W: Write>(writer: W, dir: &Path, name: &str, root: &SafeRoot) -> io::Result<()> {
+    let mut sink = ZipSink(ZipWriter::new_stream(writer));
+    walk(dir, name, root, &mut HashSet::new(), &mut sink)?;
+    sink.0.finish().map_err(io::Error::other)?.into_inner().flush()
+}
+
+/// Depth-first walk that applies the same symlink policy as the listing and
+/// never enters the same directory twice (guards against symlink loops)
+pub(super) fn walk(
+    dir: &Path,
+    prefix: &str,
+    root: &SafeRoot,
+    visited: &mut HashSet<PathBuf>,
+    sink: &mut dyn ArchiveSink,
+) -> io::Result<()> {
+    if !visited.insert(dir.canonicalize()?) {
+        return Ok(());
+    }
+    sink.add_dir(prefix, dir, &fs::metadata(dir)?)?;
+
+    let mut entries: Vec<_> = fs::read_dir(dir)?.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        if !root.permits(&path) {
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if metadata.is_dir() {
+            walk(&path, &name, root, visited, sink)?;
+        } else if metadata.is_file() {
+            sink.add_file(&name, &path, &metadata)?;
+        }
+    }
+    Ok(())
+}
+
+struct TarSink<W: Write>(tar::Builder<W>);
+
+impl<W: Write> ArchiveSink for TarSink<W> {
+    fn add_dir(&mut self, name: &str, _path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(metadata);
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        self.0.append_data(&mut header, format!("{}/", name), io::empty())
+    }
+
+    fn add_file(&mut self, name: &str, path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(metadata);
+        self.0.append_data(&mut header, name, fs::File::open(path)?)
+    }
+}
+
+struct ZipSink<W: Write>(ZipWriter<StreamWriter<W>>);
+
+impl<W: Write> ZipSink<W> {
+    fn options(metadata: &fs::Metadata) -> SimpleFileOptions {
+        // Stored, not deflated: shared folders are mostly already-compressed
+        // data, and tar.gz is there for anyone who wants compression
+        let mut options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(metadata.len() > u32::MAX as u64);
+        if let Some(time) = metadata.modified().ok().and_then(zip_time) {
+            options = options.last_modified_time(time);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(metadata.permissions().mode());
+        }
+        options
+    }
+}
+
+impl<W: Write> ArchiveSink for ZipSink<W> {
+    fn add_dir(&mut self, name: &str, _path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+        self.0
+            .add_directory(name, Self::options(metadata))
+            .map_err(io::Error::other)
+    }
+
+    fn add_file(&mut self, name: &str, path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+        self.0
+            .start_file(name, Self::options(metadata))
+            .map_err(io::Error::other)?;
+        io::copy(&mut fs::File::open(path)?, &mut self.0)?;
+        Ok(())
+    }
+}
+
+fn zip_time(time: std::time::SystemTime) -> Option<zip::DateTime> {
+    let local: DateTime<Local> = time.into();
+    zip::DateTime::from_date_and_time(
+        u16::try_from(local.year()).ok()?,
+        local.month() as u8,
+        local.day() as u8,
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second() as u8,
+    )
+    .ok()
+}
+
+// Symlink fixtures need unix
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::server::paths::SymlinkPolicy;
+    use std::io::{Cursor, Read};
+
+    /// <tmp>/root/{a.txt, .env, sub/b.txt, sub/up -> .., escape -> ../outside}, <tmp>/outside/secret.txt
+    fn fixture() -> (tempfile::TempDir, PathBuf) {
+        use std::os::unix::fs::symlink;
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("root");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::create_dir_all(tmp.path().join("outside")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join(".env"), "dot").unwrap();
+        fs::write(root.join("sub/b.txt"), "b").unwrap();
+        fs::write(tmp.path().join("outside/secret.txt"), "secret").unwrap();
+        symlink("..", root.join("sub/up")).unwrap();
+        symlink("../outside", root.join("escape")).unwrap();
+        (tmp, root)
+    }
+
+    fn unzip(bytes: Vec<u8>) -> Vec<(String, String)> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut contents = String::new();
+                file.read_to_string(&mut contents).unwrap();
+                (file.name().to_string(), contents)
+            })
+            .collect()
+    }
+
+    fn untar(bytes: Vec<u8>) -> Vec<(String, String)> {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(Cursor::new(bytes)));
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents).unwrap();
+                (entry.path().unwrap().to_string_lossy().into_owned(), contents)
+            })
+            .collect()
+    }
+
+    fn entries(listing: &[(&str, &str)]) -> Vec<(String, String)> {
+        listing.iter().map(|(name, contents)| (name.to_string(), contents.to_string())).collect()
+    }
+
+    #[test]
+    fn round_trips_only_what_the_root_permits() {
+        let (_tmp, root) = fixture();
+        let safe = SafeRoot::new(&root, SymlinkPolicy::WithinRoot).unwrap();
+
+        // `sub/up` loops back to the root: walked once, never re-entered;
+        // `escape` leaves the root and is skipped. Dotfiles are shared like in the listing.
+        let expected = entries(&[
+            ("share/", ""),
+            ("share/.env", "dot"),
+            ("share/a.txt", "a"),
+            ("share/sub/", ""),
+            ("share/sub/b.txt", "b"),
+        ]);
+
+        let mut zip = Vec::new();
+        write_zip(&mut zip, &root, "share", &safe).unwrap();
+        assert_eq!(unzip(zip), expected);
+
+        let mut tar_gz = Vec::new();
+        write_tar_gz(&mut tar_gz, &root, "share", &safe).unwrap();
+        assert_eq!(untar(tar_gz), expected);
+    }
+
+    #[test]
+    fn follows_symlinks_only_as_far_as_the_policy_allows() {
+        let (_tmp, root) = fixture();
+
+        let mut tar_gz = Vec::new();
+        write_tar_gz(&mut tar_gz, &root, "share", &SafeRoot::new(&root, SymlinkPolicy::FollowAll).unwrap()).unwrap();
+        let names: Vec<String> = untar(tar_gz).into_iter().map(|(name, _)| name).collect();
+        assert!(names.contains(&"share/escape/secret.txt".to_string()));
+        assert!(!names.iter().any(|name| name.contains("/up/")));
+
+        let mut zip = Vec::new();
+        write_zip(&mut zip, &root, "share", &SafeRoot::new(&root, SymlinkPolicy::Deny).unwrap()).unwrap();
+        assert!(!unzip(zip).iter().any(|(name, _)| name.contains("escape") || name.contains("up")));
+    }
+}
@@ -0,0 +1,225 @@
+// 🎨 Scripts, styles and fonts for yeet's own pages, embedded in the binary so
+// they work offline and never send visitors to a third party
+use std::sync::LazyLock;
+
+use axum::{
+    body::Body,
+    extract::Path,
+    http::{header, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+
+/// Where the assets are served; the same bytes ship with every yeet
+pub const PREFIX: &str = "/_yeet/static/";
+
+/// For yeet's HTML pages: nothing may load from, connect to or post to another origin
+pub const CSP: &str = "default-src 'none'; script-src 'self'; style-src 'self' 'unsafe-inline'; \
+    font-src 'self'; img-src 'self' data:; connect-src 'self'; form-action 'self'; \
+    base-uri 'none'; frame-ancestors 'none'";
+
+/// For the admin page: like `CSP`, but the dashboard's WebAssembly may be compiled
+pub const ADMIN_CSP: &str = "default-src 'none'; script-src 'self' 'wasm-unsafe-eval'; \
+    style-src 'self' 'unsafe-inline'; font-src 'self'; img-src 'self' data:; connect-src 'self'; \
+    form-action 'self'; base-uri 'none'; frame-ancestors 'none'";
+
+struct Asset {
+    name: &'static str,
+    content_type: &'static str,
+    bytes: &'static [u8],
+}
+
+static ASSETS: &[Asset] = &[
+    Asset {
+        name: "yeet.css",
+        content_type: "text/css; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/yeet.css"),
+    },
+    Asset {
+        name: "listing.js",
+        content_type: "text/javascript; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/listing.js"),
+    },
+    Asset {
+        name: "dashboard.css",
+        content_type: "text/css; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/dashboard.css"),
+    },
+    Asset {
+        name: "dashboard.js",
+        content_type: "text/javascript; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/dashboard.js"),
+    },
+    // Output of dashboard/build.sh
+    Asset {
+        name: "dashboard/yeet_dashboard.js",
+        content_type: "text/javascript; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/dashboard/yeet_dashboard.js"),
+    },
+    Asset {
+        name: "dashboard/yeet_dashboard_bg.wasm",
+        content_type: "application/wasm",
+        bytes: include_bytes!("../../assets/static/dashboard/yeet_dashboard_bg.wasm"),
+    },
+    Asset {
+        name: "dashboard/snippets/dioxus-interpreter-js/inline0.js",
+        content_type: "text/javascript; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/dashboard/snippets/dioxus-interpreter-js/inline0.js"),
+    },
+    Asset {
+        name: "dashboard/snippets/dioxus-interpreter-js/src/js/common.js",
+        content_type: "text/javascript; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/dashboard/snippets/dioxus-interpreter-js/src/js/common.js"),
+    },
+    Asset {
+        name: "upload.js",
+        content_type: "text/javascript; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/upload.js"),
+    },
+    Asset {
+        name: "follow.js",
+        content_type: "text/javascript; charset=utf-8",
+        bytes: include_bytes!("../../assets/static/follow.js"),
+    },
+    Asset {
+        name: "fonts/DejaVuSansMono.ttf",
+        content_type: "font/ttf",
+        bytes: include_bytes!("../../assets/static/fonts/DejaVuSansMono.ttf"),
+    },
+    Asset {
+        name: "fonts/DejaVuSansMono-Bold.ttf",
+        content_type: "font/ttf",
+        bytes: include_bytes!("../../assets/static/fonts/DejaVuSansMono-Bold.ttf"),
+    },
+];
+
+/// Hash of all assets, so URLs change whenever any of them does and can be cached forever
+static VERSION: LazyLock<String> = LazyLock::new(|| {
+    let mut hasher = blake3::Hasher::new();
+    for asset in ASSETS {
+        hasher.update(asset.name.as_bytes());
+        hasher.update(asset.bytes);
+    }
+    hasher.finalize().to_hex()[..12].to_string()
+});
+
+/// Versioned URL of an embedded asset, e.g. `url("listing.js")`
+pub fn url(name: &str) -> String {
+    format!("{}{}/{}", PREFIX, *VERSION, name)
+}
+
+/// True for the URL of an embedded asset (of any version). Only these are
+/// public: everything else under `PREFIX` still needs the share credentials.
+pub fn is_asset(path: &str) -> bool {
+    path.strip_prefix(PREFIX)
+        .and_then(|rest| rest.split_once('/'))
+        .is_some_and(|(version, name)| !version.is_empty() && find(name).is_some())
+}
+
+fn find(name: &str) -> Option<&'static Asset> {
+    ASSETS.iter().find(|asset| asset.name == name)
+}
+
+/// `/_yeet/static/<version>/<name>`
+pub fn router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new().route("/_yeet/static/:version/*name", get(serve))
+}
+
+async fn serve(Path((version, name)): Path<(String, String)>) -> Response {
+    // A page cached before an upgrade may still ask for an old version: serve the
+    // current bytes, just without letting them be cached under that URL
+    let cache_control = if version == *VERSION {
+        "public, max-age=31536000, immutable"
+    } else {
+        "no-cache"
+    };
+    match find(&name) {
+        Some(asset) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, asset.content_type)
+            .header(header::CACHE_CONTROL, cache_control)
+            .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+            .body(Body::from(asset.bytes))
+            .unwrap(),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("Not found"))
+            .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_only_reference_embedded_assets() {
+        let css = std::str::from_utf8(ASSETS[0].bytes).unwrap();
+        for font in css.split("url('").skip(1) {
+            let name = font.split('\'').next().unwrap();
+            assert!(ASSETS.iter().any(|asset| asset.name == name), "{} is not embedded", name);
+        }
+        assert!(url("yeet.css").starts_with(PREFIX));
+        assert!(!CSP.contains("http"));
+        assert_eq!(ADMIN_CSP, CSP.replace("script-src 'self'", "script-src 'self' 'wasm-unsafe-eval'"));
+    }
+
+    /// Relative `import ... from './x.js'` targets of an embedded module
+    fn imports(asset: &Asset) -> Vec<String> {
+        let source = std::str::from_utf8(asset.bytes).unwrap();
+        let dir = asset.name.rsplit_once('/').map_or("", |(dir, _)| dir);
+        source
+            .lines()
+            .filter(|line| line.starts_with("import "))
+            .filter_map(|line| line.split(['\'', '"']).nth(1))
+            .map(|target| {
+                let target = target.strip_prefix("./").expect("relative import");
+                if dir.is_empty() {
+                    target.to_string()
+                } else {
+                    format!("{}/{}", dir, target)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dashboard_modules_only_import_embedded_assets() {
+        let loader = find("dashboard.js").unwrap();
+        assert_eq!(imports(loader), ["dashboard/yeet_dashboard.js"]);
+
+        let mut modules = vec![loader];
+        while let Some(module) = modules.pop() {
+            for name in imports(module) {
+                let imported = find(&name).unwrap_or_else(|| panic!("{} imports {}, which is not embedded", module.name, name));
+                modules.push(imported);
+            }
+        }
+        let bindings = std::str::from_utf8(find("dashboard/yeet_dashboard.js").unwrap().bytes).unwrap();
+        assert!(bindings.contains("new URL('yeet_dashboard_bg.wasm', import.meta.url)"));
+        assert!(find("dashboard/yeet_dashboard_bg.wasm").is_some());
+    }
+
+    #[test]
+    fn only_embedded_assets_are_public() {
+        assert!(is_asset(&url("yeet.css")));
+        assert!(is_asset(&url("fonts/DejaVuSansMono.ttf")));
+        // Pages cached before an upgrade still get their assets
+        assert!(is_asset("/_yeet/static/0123456789ab/listing.js"));
+
+        for path in [
+            "/_yeet/static/",
+            "/_yeet/static/yeet.css",
+            "//_yeet/static/v/yeet.css",
+            "/_yeet/static//yeet.css",
+            "/_yeet/static/v/missing.js",
+            "/_yeet/static/v/fonts/",
+            "/_yeet/static/v/../../admin",
+            "/_yeet/static/v/yeet.css/extra",
+            "/admin",
+        ] {
+            assert!(!is_asset(path), "{}", path);
+        }
+    }
+}
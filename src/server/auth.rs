@@ -0,0 +1,491 @@
+// 🔐 Access control: Basic auth, secret links and a separate admin credential
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{OriginalUri, Request, State},
+    http::{header, request::Parts, uri::PathAndQuery, HeaderMap, HeaderValue, StatusCode, Uri},
+    middleware::Next,
+    response::Response,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use super::assets;
+
+/// Cookie remembering a valid secret link so relative navigation keeps working
+const KEY_COOKIE: &str = "yeet_key";
+/// Cookie remembering the admin credential for the dashboard's API calls
+const ADMIN_COOKIE: &str = "yeet_admin";
+/// Query parameter accepted in place of the secret path prefix
+const KEY_PARAM: &str = "key";
+/// Query parameter carrying the admin credential
+const ADMIN_PARAM: &str = "admin_token";
+
+/// Everything but RFC 3986 unreserved characters, so credentials survive
+/// query strings and cookie values whatever they contain
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Prefix for yeet's own routes, which nothing shared or proxied can shadow
+pub const NAMESPACE: &str = "/_yeet";
+/// Routes only the share owner may see, under NAMESPACE (and at the top
+/// level unless proxying)
+const ADMIN_PATHS: &[&str] = &["/admin", "/api/stats", "/api/logs"];
+
+/// Set on requests whose `Authorization` header carried the share password
+#[derive(Clone)]
+struct OwnAuthorization;
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    /// HTTP Basic auth password for recipients (any username)
+    pub password: Option<String>,
+    /// Token that must appear as the first path segment or `?key=`
+    pub secret: Option<String>,
+    /// Credential for the admin dashboard and its API
+    pub admin_token: String,
+    /// Proxy mode: the bare `/admin` and `/api/*` paths belong to the app
+    pub proxied: bool,
+}
+
+impl AuthConfig {
+    fn is_admin_path(&self, path: &str) -> bool {
+        let bare = match path.strip_prefix(NAMESPACE) {
+            Some(rest) => rest,
+            None if self.proxied => return false,
+            None => path,
+        };
+        ADMIN_PATHS.contains(&bare)
+    }
+}
+
+/// 128-bit random token, hex encoded
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("Failed to gather randomness");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Dashboard link that logs the owner in
+pub fn admin_url(base_url: &str, auth: &AuthConfig) -> String {
+    let prefix = if auth.proxied { NAMESPACE } else { "" };
+    let token = utf8_percent_encode(&auth.admin_token, UNRESERVED);
+    format!("{}{}/admin?{}={}", base_url, prefix, ADMIN_PARAM, token)
+}
+
+/// Middleware wrapped around the whole router (before routing, since it may
+/// strip the secret prefix from the path)
+pub async fn enforce(State(auth): State<Arc<AuthConfig>>, mut req: Request, next: Next) -> Response {
+    let mut remember_key = false;
+    let mut secret_ok = false;
+
+    if let Some(secret) = &auth.secret {
+        if let Some(stripped) = strip_secret_prefix(req.uri(), secret) {
+            // Handlers that build URLs (tus Location) need the path the client used
+            let original = OriginalUri(req.uri().clone());
+            req.extensions_mut().insert(original);
+            *req.uri_mut() = stripped;
+            remember_key = true;
+            secret_ok = true;
+        } else if query_param(req.uri(), KEY_PARAM).is_some_and(|key| ct_eq(&key, secret)) {
+            remember_key = true;
+            secret_ok = true;
+        } else {
+            secret_ok = cookie(req.headers(), KEY_COOKIE).is_some_and(|key| ct_eq(&key, secret));
+        }
+    }
+
+    // Embedded page assets are the same in every yeet, and both the admin
+    // dashboard and recipient pages load them
+    if assets::is_asset(req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    // Admin routes only check the admin credential, never the recipient ones
+    if auth.is_admin_path(req.uri().path()) {
+        let from_query = query_param(req.uri(), ADMIN_PARAM).is_some_and(|t| ct_eq(&t, &auth.admin_token));
+        let authorized = from_query
+            || cookie(req.headers(), ADMIN_COOKIE).is_some_and(|t| ct_eq(&t, &auth.admin_token))
+            || basic_password(req.headers()).is_some_and(|p| ct_eq(&p, &auth.admin_token));
+        if !authorized {
+            // A Basic prompt would give away a share hidden behind a secret link
+            return match auth.secret {
+                Some(_) => not_found(),
+                None => unauthorized("yeet admin"),
+            };
+        }
+        let mut response = next.run(req).await;
+        if from_query {
+            set_cookie(&mut response, ADMIN_COOKIE, &auth.admin_token);
+        }
+        return response;
+    }
+
+    if auth.secret.is_some() && !secret_ok {
+        // Don't reveal that anything is being shared here
+        return not_found();
+    }
+
+    if let Some(password) = &auth.password {
+        if !basic_password(req.headers()).is_some_and(|p| ct_eq(&p, password)) {
+            return unauthorized("yeet");
+        }
+        req.extensions_mut().insert(OwnAuthorization);
+    }
+
+    let mut response = next.run(req).await;
+    if remember_key {
+        if let Some(secret) = &auth.secret {
+            set_cookie(&mut response, KEY_COOKIE, secret);
+        }
+    }
+    response
+}
+
+/// `/<secret>/rest?q` -> `/rest?q`, or `None` if the path doesn't start with the secret
+fn strip_secret_prefix(uri: &Uri, secret: &str) -> Option<Uri> {
+    let path = uri.path();
+    let rest = path.strip_prefix('/')?;
+    let (first, rest) = rest.split_once('/').map_or((rest, ""), |(first, rest)| (first, rest));
+    if !ct_eq(first, secret) {
+        return None;
+    }
+
+    let new_path_and_query = match uri.query() {
+        Some(query) => format!("/{}?{}", rest, query),
+        None => format!("/{}", rest),
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(PathAndQuery::try_from(new_path_and_query).ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+/// Percent-decoded query parameter
+fn query_param(uri: &Uri, name: &str) -> Option<String> {
+    uri.query()?.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == name).then(|| percent_decode_str(value).decode_utf8().ok().map(String::from))?
+    })
+}
+
+/// Percent-decoded cookie value, as written by `set_cookie`
+fn cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            (key == name).then(|| percent_decode_str(value).decode_utf8().ok().map(String::from))?
+        })
+}
+
+/// Password from an `Authorization: Basic` header (the username is ignored)
+fn basic_password(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let encoded = value.strip_prefix("Basic ").or_else(|| value.strip_prefix("basic "))?;
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    decoded.split_once(':').map(|(_, password)| password.to_string())
+}
+
+/// Drop yeet's own cookies and Basic auth from a request before it leaves the
+/// daemon, so proxied apps never see the share or admin credentials
+pub fn strip_own_credentials(parts: &mut Parts) {
+    strip_own_cookies(&mut parts.headers);
+    // Basic auth meant for the app itself is passed through untouched
+    if parts.extensions.remove::<OwnAuthorization>().is_some() {
+        parts.headers.remove(header::AUTHORIZATION);
+    }
+}
+
+fn strip_own_cookies(headers: &mut HeaderMap) {
+    let kept: Vec<String> = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .map(str::trim)
+        .filter(|pair| {
+            let name = pair.split_once('=').map_or(*pair, |(name, _)| name);
+            !pair.is_empty() && name != KEY_COOKIE && name != ADMIN_COOKIE
+        })
+        .map(str::to_string)
+        .collect();
+
+    headers.remove(header::COOKIE);
+    if !kept.is_empty() {
+        if let Ok(value) = HeaderValue::from_str(&kept.join("; ")) {
+            headers.insert(header::COOKIE, value);
+        }
+    }
+}
+
+fn set_cookie(response: &mut Response, name: &str, value: &str) {
+    let value = utf8_percent_encode(value, UNRESERVED);
+    let cookie = format!("{}={}; Path=/; HttpOnly; SameSite=Lax", name, value);
+    if let Ok(value) = HeaderValue::from_str(&cookie) {
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+}
+
+fn not_found() -> Response {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("Not found"))
+        .unwrap()
+}
+
+fn unauthorized(realm: &str) -> Response {
+    Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .header(header::WWW_AUTHENTICATE, format!("Basic realm=\"{}\", charset=\"UTF-8\"", realm))
+        .body(Body::from("Unauthorized"))
+        .unwrap()
+}
+
+/// Comparison whose running time doesn't depend on where the inputs differ
+fn ct_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tower::ServiceExt;
+
+    /// What a proxied app would receive: its request headers after stripping
+    async fn forwarded(password: Option<&str>, headers: &[(header::HeaderName, &str)]) -> (StatusCode, HeaderMap) {
+        let auth = AuthConfig {
+            password: password.map(str::to_string),
+            secret: None,
+            admin_token: "admin".to_string(),
+            proxied: false,
+        };
+        let app = Router::new()
+            .route(
+                "/",
+                get(|req: Request| async move {
+                    let (mut parts, _) = req.into_parts();
+                    strip_own_credentials(&mut parts);
+                    let mut response = Response::new(Body::empty());
+                    *response.headers_mut() = parts.headers;
+                    response
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(Arc::new(auth), enforce));
+
+        let mut req = Request::builder().uri("/");
+        for (name, value) in headers {
+            req = req.header(name, *value);
+        }
+        let response = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        (response.status(), response.headers().clone())
+    }
+
+    /// A share behind `enforce` whose handler echoes the URI it was routed by
+    /// and the one the client sent
+    fn guarded(auth: AuthConfig) -> Router {
+        Router::new()
+            .fallback(|uri: Uri, OriginalUri(original): OriginalUri| async move { format!("{} {}", uri, original) })
+            .layer(axum::middleware::from_fn_with_state(Arc::new(auth), enforce))
+    }
+
+    fn config(password: Option<&str>, secret: Option<&str>, admin_token: &str) -> AuthConfig {
+        AuthConfig {
+            password: password.map(str::to_string),
+            secret: secret.map(str::to_string),
+            admin_token: admin_token.to_string(),
+            proxied: false,
+        }
+    }
+
+    async fn send(app: &Router, uri: &str, headers: &[(header::HeaderName, &str)]) -> Response {
+        let mut req = Request::builder().uri(uri);
+        for (name, value) in headers {
+            req = req.header(name, *value);
+        }
+        app.clone().oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    fn basic(credentials: &str) -> String {
+        format!("Basic {}", STANDARD.encode(credentials))
+    }
+
+    #[tokio::test]
+    async fn proxied_apps_never_see_yeets_credentials() {
+        let own = basic("anyone:hunter2");
+        let cookies = "yeet_key=k; theme=dark; yeet_admin=admin";
+        let (status, headers) = forwarded(
+            Some("hunter2"),
+            &[(header::AUTHORIZATION, &own), (header::COOKIE, cookies)],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(headers.get(header::AUTHORIZATION).is_none());
+        assert_eq!(headers[header::COOKIE], "theme=dark");
+
+        // Without --password the header is the app's own business
+        let app_login = basic("alice:app-secret");
+        let (status, headers) = forwarded(None, &[(header::AUTHORIZATION, &app_login)]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::AUTHORIZATION], app_login.as_str());
+
+        let (status, _) = forwarded(Some("hunter2"), &[(header::AUTHORIZATION, &app_login)]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn only_real_assets_skip_the_password() {
+        let auth = AuthConfig {
+            password: Some("hunter2".to_string()),
+            secret: None,
+            admin_token: "admin".to_string(),
+            proxied: false,
+        };
+        let app = assets::router()
+            .fallback(|| async { "page" })
+            .layer(axum::middleware::from_fn_with_state(Arc::new(auth), enforce));
+        let status = |path: String| {
+            let app = app.clone();
+            async move {
+                let req = Request::builder().uri(path).body(Body::empty()).unwrap();
+                app.oneshot(req).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status(assets::url("yeet.css")).await, StatusCode::OK);
+        for path in [
+            assets::url("secret.txt"),
+            format!("{}v/", assets::PREFIX),
+            format!("{}v/fonts/../../index.html", assets::PREFIX),
+            format!("{}anything", assets::PREFIX),
+        ] {
+            assert_eq!(status(path.clone()).await, StatusCode::UNAUTHORIZED, "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn admin_passwords_survive_urls_and_cookies() {
+        let password = "p@ss w&rd=100%;\"ü\"";
+        let auth = config(None, None, password);
+        let url = admin_url("", &auth);
+        let app = guarded(auth);
+        assert!(!url.contains(' ') && !url.contains('&'), "{}", url);
+
+        let response = send(&app, &url, &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let set_cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        let cookie = set_cookie.split(';').next().unwrap();
+        assert!(!cookie.contains(' ') && !cookie.contains('"'), "{}", cookie);
+
+        let response = send(&app, "/api/stats", &[(header::COOKIE, cookie)]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(&app, "/admin?admin_token=p@ss", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    async fn text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn secret_links_unlock_the_share() {
+        let app = guarded(config(None, Some("s3cret"), "admin"));
+
+        // The prefix is stripped before routing, but handlers can still see it
+        let response = send(&app, "/s3cret/dir/a.txt?x=1", &[]).await;
+        assert_eq!(text(response).await, "/dir/a.txt?x=1 /s3cret/dir/a.txt?x=1");
+
+        let response = send(&app, "/s3cret", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::SET_COOKIE].to_str().unwrap().starts_with("yeet_key=s3cret;"));
+        assert_eq!(text(response).await, "/ /s3cret");
+
+        let response = send(&app, "/a.txt?key=s3cret", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(header::SET_COOKIE));
+        assert_eq!(text(response).await, "/a.txt?key=s3cret /a.txt?key=s3cret");
+
+        // The cookie alone is enough, and isn't set again
+        let response = send(&app, "/a.txt", &[(header::COOKIE, "yeet_key=s3cret")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(header::SET_COOKIE));
+
+        for (uri, cookie) in [
+            ("/a.txt", ""),
+            ("/wrong/a.txt", ""),
+            ("/s3cre/a.txt", ""),
+            ("/a.txt?key=wrong", ""),
+            ("/a.txt", "yeet_key=wrong"),
+        ] {
+            let response = send(&app, uri, &[(header::COOKIE, cookie)]).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{} {}", uri, cookie);
+            assert!(!response.headers().contains_key(header::WWW_AUTHENTICATE));
+        }
+    }
+
+    #[tokio::test]
+    async fn admin_and_recipient_credentials_are_separate() {
+        let app = guarded(config(Some("hunter2"), None, "admin"));
+        let recipient = basic("x:hunter2");
+        let admin = basic("x:admin");
+
+        for path in ADMIN_PATHS {
+            let response = send(&app, path, &[(header::AUTHORIZATION, &recipient)]).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", path);
+            assert!(response.headers()[header::WWW_AUTHENTICATE].to_str().unwrap().contains("yeet admin"));
+            let response = send(&app, path, &[(header::AUTHORIZATION, &admin)]).await;
+            assert_eq!(response.status(), StatusCode::OK, "{}", path);
+        }
+        let response = send(&app, "/admin?admin_token=admin", &[]).await;
+        assert!(response.headers()[header::SET_COOKIE].to_str().unwrap().starts_with("yeet_admin=admin;"));
+
+        let response = send(&app, "/a.txt", &[(header::AUTHORIZATION, &admin)]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(&app, "/a.txt", &[(header::COOKIE, "yeet_admin=admin")]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(&app, "/a.txt", &[(header::AUTHORIZATION, &recipient)]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Behind a secret link the admin routes don't admit to existing either
+        let app = guarded(config(None, Some("s3cret"), "admin"));
+        let response = send(&app, "/admin", &[(header::COOKIE, "yeet_key=s3cret")]).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(!response.headers().contains_key(header::WWW_AUTHENTICATE));
+        let response = send(&app, "/s3cret/admin?key=s3cret", &[]).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = send(&app, "/admin?admin_token=admin", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(&app, "/a.txt?key=admin", &[]).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn proxied_apps_keep_their_own_admin_paths() {
+        let auth = AuthConfig {
+            proxied: true,
+            ..config(None, None, "admin")
+        };
+        assert_eq!(admin_url("https://x", &auth), "https://x/_yeet/admin?admin_token=admin");
+        let app = guarded(auth);
+
+        for path in ["/admin", "/api/stats", "/api/logs"] {
+            assert_eq!(send(&app, path, &[]).await.status(), StatusCode::OK, "{}", path);
+            let namespaced = format!("{}{}", NAMESPACE, path);
+            assert_eq!(send(&app, &namespaced, &[]).await.status(), StatusCode::UNAUTHORIZED, "{}", namespaced);
+        }
+
+        // Outside proxy mode both spellings are yeet's
+        let app = guarded(config(None, None, "admin"));
+        for path in ["/admin", "/_yeet/admin", "/_yeet/api/logs"] {
+            assert_eq!(send(&app, path, &[]).await.status(), StatusCode::UNAUTHORIZED, "{}", path);
+        }
+    }
+}
@@ -0,0 +1,418 @@
+// 🔏 SHA-256 (and optionally BLAKE3) checksums, hashed on a background thread
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, LazyLock, Mutex,
+    },
+    time::UNIX_EPOCH,
+};
+
+use axum::{
+    body::Body,
+    http::{header, response::Builder as ResponseBuilder, HeaderMap, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use tokio::sync::Notify;
+
+use super::{
+    archive::{self, ArchiveSink},
+    paths::SafeRoot,
+};
+
+/// Read size while hashing
+const CHUNK_SIZE: usize = 1024 * 1024;
+/// How long clients are asked to wait while a manifest is still being hashed
+const RETRY_AFTER_SECS: u64 = 5;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static BLAKE3: AtomicBool = AtomicBool::new(false);
+static CACHE: LazyLock<Mutex<HashMap<PathBuf, Entry>>> = LazyLock::new(Default::default);
+/// Files waiting for the hashing thread; requested files jump the queue
+static QUEUE: LazyLock<(Mutex<VecDeque<PathBuf>>, Condvar)> = LazyLock::new(Default::default);
+/// Woken whenever a file finishes hashing
+static HASHED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Hex digests of one version of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksums {
+    pub sha256: String,
+    /// Only with `--blake3`
+    pub blake3: Option<String>,
+}
+
+impl Checksums {
+    /// `Repr-Digest` (RFC 9530) plus the older `Digest` (RFC 3230), both
+    /// describing the whole unencoded file
+    pub fn apply(&self, builder: ResponseBuilder) -> ResponseBuilder {
+        let Some(sha256) = hex_to_base64(&self.sha256) else {
+            return builder;
+        };
+        builder
+            .header("repr-digest", format!("sha-256=:{}:", sha256))
+            .header("digest", format!("SHA-256={}", sha256))
+    }
+}
+
+/// Where hashing of a file has got to
+#[derive(Debug, Clone)]
+pub enum Hashing {
+    Ready(Checksums),
+    Pending,
+    /// Unreadable, or checksums are turned off
+    Unavailable,
+}
+
+struct Entry {
+    /// Size and mtime the result belongs to
+    version: (u64, u128),
+    hashing: Hashing,
+}
+
+/// Turn checksums on and start the hashing thread. Call once at daemon start.
+pub fn enable(blake3: bool) {
+    BLAKE3.store(blake3, Ordering::Relaxed);
+    if !ENABLED.swap(true, Ordering::Relaxed) {
+        std::thread::Builder::new()
+            .name("yeet-checksums".to_string())
+            .spawn(worker)
+            .expect("Failed to start checksum thread");
+    }
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn blake3_enabled() -> bool {
+    BLAKE3.load(Ordering::Relaxed)
+}
+
+/// Current checksums for `path`, queueing it for hashing when there are none
+/// for this version of the file yet
+pub fn status(path: &Path, metadata: &fs::Metadata) -> Hashing {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return Hashing::Unavailable;
+    }
+    let version = version(metadata);
+    let mut cache = CACHE.lock().unwrap();
+    match cache.get(path) {
+        Some(entry) if entry.version == version => entry.hashing.clone(),
+        _ => {
+            cache.insert(path.to_path_buf(), Entry { version, hashing: Hashing::Pending });
+            enqueue(path.to_path_buf(), true);
+            Hashing::Pending
+        }
+    }
+}
+
+/// Checksums if they are ready, without waiting
+pub fn lookup(path: &Path, metadata: &fs::Metadata) -> Option<Checksums> {
+    match status(path, metadata) {
+        Hashing::Ready(checksums) => Some(checksums),
+        _ => None,
+    }
+}
+
+/// Wait until `path` is hashed (`None` if it can't be)
+pub async fn ready(path: &Path) -> Option<Checksums> {
+    loop {
+        let hashed = HASHED.notified();
+        let metadata = tokio::fs::metadata(path).await.ok()?;
+        match status(path, &metadata) {
+            Hashing::Ready(checksums) => return Some(checksums),
+            Hashing::Unavailable => return None,
+            Hashing::Pending => hashed.await,
+        }
+    }
+}
+
+/// Queue every file under `root` (same rules as archives) behind anything
+/// clients ask for, so manifests and listings fill in ahead of time
+pub fn precompute(root: SafeRoot) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    tokio::task::spawn_blocking(move || {
+        for (_, path, metadata) in list_files(&root).unwrap_or_default() {
+            let version = version(&metadata);
+            let mut cache = CACHE.lock().unwrap();
+            if cache.get(&path).is_none_or(|entry| entry.version != version) {
+                cache.insert(path.clone(), Entry { version, hashing: Hashing::Pending });
+                enqueue(path, false);
+            }
+        }
+    });
+}
+
+fn enqueue(path: PathBuf, urgent: bool) {
+    let (queue, wakeup) = &*QUEUE;
+    let mut queue = queue.lock().unwrap();
+    if urgent {
+        queue.push_front(path);
+    } else {
+        queue.push_back(path);
+    }
+    wakeup.notify_one();
+}
+
+fn worker() {
+    let (queue, wakeup) = &*QUEUE;
+    loop {
+        let path = {
+            let mut queue = queue.lock().unwrap();
+            loop {
+                match queue.pop_front() {
+                    Some(path) => break path,
+                    None => queue = wakeup.wait(queue).unwrap(),
+                }
+            }
+        };
+        hash_queued(path);
+        HASHED.notify_waiters();
+    }
+}
+
+fn hash_queued(path: PathBuf) {
+    // Already done (queued twice), or superseded by a newer version
+    let Some(expected) = CACHE
+        .lock()
+        .unwrap()
+        .get(&path)
+        .filter(|entry| matches!(entry.hashing, Hashing::Pending))
+        .map(|entry| entry.version)
+    else {
+        return;
+    };
+    let before = fs::metadata(&path).map(|m| version(&m)).ok();
+    let result = hash_file(&path, blake3_enabled());
+    let after = fs::metadata(&path).map(|m| version(&m)).ok();
+
+    let mut cache = CACHE.lock().unwrap();
+    if before != Some(expected) || after != before {
+        // Changed while we were reading; the next request queues it again
+        cache.remove(&path);
+        return;
+    }
+    let hashing = match result {
+        Ok(checksums) => Hashing::Ready(checksums),
+        Err(_) => Hashing::Unavailable,
+    };
+    cache.insert(path, Entry { version: expected, hashing });
+}
+
+/// One pass over the file feeding every enabled hash
+fn hash_file(path: &Path, with_blake3: bool) -> io::Result<Checksums> {
+    let mut file = fs::File::open(path)?;
+    let mut sha256 = Sha256::new();
+    let mut blake3 = with_blake3.then(blake3::Hasher::new);
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        sha256.update(&buf[..n]);
+        if let Some(blake3) = &mut blake3 {
+            blake3.update(&buf[..n]);
+        }
+    }
+    Ok(Checksums {
+        sha256: to_hex(&sha256.finalize()),
+        blake3: blake3.map(|hasher| hasher.finalize().to_hex().to_string()),
+    })
+}
+
+fn version(metadata: &fs::Metadata) -> (u64, u128) {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    (metadata.len(), mtime)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_to_base64(hex: &str) -> Option<String> {
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some(STANDARD.encode(bytes))
+}
+
+/// Every file under `root` as (relative name, path, metadata)
+fn list_files(root: &SafeRoot) -> io::Result<Vec<(String, PathBuf, fs::Metadata)>> {
+    struct Files(Vec<(String, PathBuf, fs::Metadata)>);
+
+    impl ArchiveSink for Files {
+        fn add_dir(&mut self, _name: &str, _path: &Path, _metadata: &fs::Metadata) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn add_file(&mut self, name: &str, path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+            let name = name.strip_prefix("./").unwrap_or(name).to_string();
+            self.0.push((name, path.to_path_buf(), metadata.clone()));
+            Ok(())
+        }
+    }
+
+    let mut files = Files(Vec::new());
+    archive::walk(root.path(), ".", root, &mut HashSet::new(), &mut files)?;
+    Ok(files.0)
+}
+
+#[derive(Clone, Copy)]
+enum Manifest {
+    Sha256,
+    Blake3,
+}
+
+impl Manifest {
+    fn file_name(self) -> &'static str {
+        match self {
+            Manifest::Sha256 => "SHA256SUMS",
+            Manifest::Blake3 => "B3SUMS",
+        }
+    }
+
+    fn digest(self, checksums: &Checksums) -> Option<&str> {
+        match self {
+            Manifest::Sha256 => Some(&checksums.sha256),
+            Manifest::Blake3 => checksums.blake3.as_deref(),
+        }
+    }
+}
+
+/// `/SHA256SUMS` (and `/B3SUMS` with `--blake3`) for a directory share, in
+/// the format `sha256sum -c` / `b3sum -c` check
+pub fn router<S: Clone + Send + Sync + 'static>(root: SafeRoot) -> Router<S> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return Router::new();
+    }
+    let root = Arc::new(root);
+    let mut router = Router::new();
+    let mut manifests = vec![Manifest::Sha256];
+    if blake3_enabled() {
+        manifests.push(Manifest::Blake3);
+    }
+    for manifest in manifests {
+        let root = root.clone();
+        router = router.route(
+            &format!("/{}", manifest.file_name()),
+            get(move |headers: HeaderMap| manifest_response(root.clone(), manifest, headers)),
+        );
+    }
+    router
+}
+
+async fn manifest_response(root: Arc<SafeRoot>, manifest: Manifest, headers: HeaderMap) -> Response {
+    // A real manifest shipped in the shared directory wins over ours
+    let shipped = root.path().join(manifest.file_name());
+    if shipped.is_file() {
+        return super::files::serve_file(&shipped, &headers, true).await;
+    }
+
+    let files = match tokio::task::spawn_blocking(move || list_files(&root)).await {
+        Ok(Ok(files)) => files,
+        _ => return plain(StatusCode::INTERNAL_SERVER_ERROR, "Failed to list shared files\n".to_string()),
+    };
+
+    let mut body = String::new();
+    let mut pending = 0;
+    for (name, path, metadata) in &files {
+        match status(path, metadata) {
+            Hashing::Ready(checksums) => {
+                if let Some(digest) = manifest.digest(&checksums) {
+                    body.push_str(&manifest_line(digest, name));
+                }
+            }
+            Hashing::Pending => pending += 1,
+            // Unreadable files are left out rather than failing the whole list
+            Hashing::Unavailable => {}
+        }
+    }
+
+    if pending > 0 {
+        return Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header(header::RETRY_AFTER, RETRY_AFTER_SECS)
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(Body::from(format!(
+                "Still hashing: {} of {} files done, try again shortly\n",
+                files.len() - pending,
+                files.len()
+            )))
+            .unwrap();
+    }
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// `<digest>  <name>`, escaped the way coreutils does for names containing a
+/// backslash or newline
+fn manifest_line(digest: &str, name: &str) -> String {
+    if name.contains(['\\', '\n', '\r']) {
+        let escaped = name.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r");
+        format!("\\{}  {}\n", digest, escaped)
+    } else {
+        format!("{}  {}\n", digest, name)
+    }
+}
+
+fn plain(status: StatusCode, message: String) -> Response {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(message))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_and_formats_digests() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("weights.bin");
+        fs::write(&path, "abc").unwrap();
+
+        let checksums = hash_file(&path, true).unwrap();
+        assert_eq!(checksums.sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            checksums.blake3.as_deref(),
+            Some("6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85")
+        );
+        assert!(hash_file(&path, false).unwrap().blake3.is_none());
+
+        let response = checksums.apply(Response::builder()).body(()).unwrap();
+        assert_eq!(
+            response.headers()["repr-digest"],
+            "sha-256=:ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=:"
+        );
+        assert_eq!(response.headers()["digest"], "SHA-256=ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=");
+    }
+
+    #[test]
+    fn escapes_manifest_names() {
+        assert_eq!(manifest_line("ab", "models/a.bin"), "ab  models/a.bin\n");
+        assert_eq!(manifest_line("ab", "odd\nname\\x"), "\\ab  odd\\nname\\\\x\n");
+    }
+}
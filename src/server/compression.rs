@@ -0,0 +1,226 @@
+// 🗜️ Negotiated gzip/brotli/zstd for text responses, plus precompressed sidecars
+use std::path::{Path, PathBuf};
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, Response},
+    response::Response as AxumResponse,
+};
+use tower_http::compression::{
+    predicate::{Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+/// Bodies smaller than this aren't worth the encoder's framing overhead
+const MIN_SIZE: u16 = 1024;
+
+/// Non-`text/*` types that compress well
+const COMPRESSIBLE_TYPES: &[&str] = &[
+    "application/json",
+    "application/x-ndjson",
+    "application/jsonl",
+    "application/javascript",
+    "application/xml",
+    "application/xhtml+xml",
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/toml",
+    "application/yaml",
+    "application/x-yaml",
+    "application/sql",
+    "application/wasm",
+    "font/ttf",
+    "image/svg+xml",
+];
+
+/// Sidecar extensions in order of preference, with their Content-Encoding
+const SIDECARS: &[(&str, &str)] = &[("br", "br"), ("zst", "zstd"), ("gz", "gzip")];
+
+/// Only compress text-like types; images, video, archives and the like are
+/// already compressed, and event streams and `no-transform` responses (live
+/// tails) must not be buffered. Files served with `Accept-Ranges` stay raw:
+/// encoding them drops their length and strong ETag, so an interrupted
+/// download could never resume (precompressed sidecars cover those).
+#[derive(Clone, Copy)]
+pub struct Compressible;
+
+impl Predicate for Compressible {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: http_body::Body,
+    {
+        let no_transform = response
+            .headers()
+            .get(header::CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.split(',').any(|d| d.trim().eq_ignore_ascii_case("no-transform")));
+        if no_transform || response.headers().contains_key(header::ACCEPT_RANGES) {
+            return false;
+        }
+        let Some(content_type) = response.headers().get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+            return false;
+        };
+        let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        (essence.starts_with("text/") && essence != "text/event-stream") || COMPRESSIBLE_TYPES.contains(&essence.as_str())
+    }
+}
+
+/// Compression for the whole router: listings, pages and the JSON APIs
+pub fn layer() -> CompressionLayer<tower_http::compression::predicate::And<SizeAbove, Compressible>> {
+    CompressionLayer::new().compress_when(SizeAbove::new(MIN_SIZE).and(Compressible))
+}
+
+/// An encoded body is a different representation from the file on disk, so
+/// its ETag can only be weak (this also stops If-Range from mixing encoded
+/// and raw bytes) and digests of the raw file no longer describe it. Runs
+/// outside `layer()`.
+pub async fn adjust_for_encoding(mut response: AxumResponse) -> AxumResponse {
+    let headers = response.headers_mut();
+    if headers.contains_key(header::CONTENT_ENCODING) {
+        if let Some(etag) = headers.get(header::ETAG).and_then(|v| v.to_str().ok()) {
+            if !etag.starts_with("W/") {
+                let weak = HeaderValue::from_str(&format!("W/{}", etag)).unwrap();
+                headers.insert(header::ETAG, weak);
+            }
+        }
+        headers.remove("repr-digest");
+        headers.remove("digest");
+    }
+    response
+}
+
+/// A precompressed copy of a file (`foo.csv.zst` next to `foo.csv`)
+pub struct Sidecar {
+    pub path: PathBuf,
+    pub encoding: &'static str,
+}
+
+/// Whether any sidecar exists for `path`, regardless of what the client accepts
+pub fn has_sidecar(path: &Path) -> bool {
+    SIDECARS.iter().any(|(ext, _)| sidecar_path(path, ext).is_file())
+}
+
+/// Pick the best sidecar the client accepts. Sidecars older than the file
+/// itself are stale and ignored.
+pub fn find_sidecar(path: &Path, original: &std::fs::Metadata, headers: &HeaderMap) -> Option<Sidecar> {
+    let accept = headers.get(header::ACCEPT_ENCODING)?.to_str().ok()?;
+    let modified = original.modified().ok()?;
+
+    SIDECARS
+        .iter()
+        .filter(|(_, encoding)| accepts(accept, encoding))
+        .find_map(|(ext, encoding)| {
+            let candidate = sidecar_path(path, ext);
+            let metadata = std::fs::metadata(&candidate).ok()?;
+            (metadata.is_file() && metadata.modified().ok()? >= modified).then_some(Sidecar {
+                path: candidate,
+                encoding,
+            })
+        })
+}
+
+fn sidecar_path(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+/// Accept-Encoding lookup: an explicit entry wins over `*`, and q=0 refuses
+fn accepts(accept: &str, encoding: &str) -> bool {
+    let mut wildcard = false;
+    for item in accept.split(',') {
+        let mut params = item.split(';');
+        let coding = params.next().unwrap_or_default().trim();
+        let refused = params.any(|param| {
+            param
+                .trim()
+                .strip_prefix("q=")
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        });
+        if coding.eq_ignore_ascii_case(encoding) {
+            return !refused;
+        }
+        if coding == "*" {
+            wildcard = !refused;
+        }
+    }
+    wildcard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_accept_encoding() {
+        assert!(accepts("gzip, deflate, br, zstd", "zstd"));
+        assert!(accepts("gzip;q=0.5, br;q=1.0", "br"));
+        assert!(!accepts("gzip, br;q=0", "br"));
+        assert!(!accepts("identity", "gzip"));
+        assert!(accepts("*", "br"));
+        assert!(!accepts("*, zstd;q=0", "zstd"));
+    }
+
+    #[test]
+    fn prefers_fresh_sidecars() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("data.csv");
+        std::fs::write(&file, "a,b\n").unwrap();
+        std::fs::write(tmp.path().join("data.csv.gz"), "gz").unwrap();
+        std::fs::write(tmp.path().join("data.csv.zst"), "zst").unwrap();
+        let metadata = std::fs::metadata(&file).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip, zstd"));
+        let sidecar = find_sidecar(&file, &metadata, &headers).unwrap();
+        assert_eq!((sidecar.path, sidecar.encoding), (tmp.path().join("data.csv.zst"), "zstd"));
+
+        headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static("br"));
+        assert!(find_sidecar(&file, &metadata, &headers).is_none());
+        assert!(find_sidecar(&file, &metadata, &HeaderMap::new()).is_none());
+        assert!(has_sidecar(&file));
+    }
+
+    #[tokio::test]
+    async fn downloads_resume_after_a_full_response() {
+        use axum::{body::Body, http::Request, routing::get, Router};
+        use tower::ServiceExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("big.csv");
+        let csv = "id,name\n".repeat(1000);
+        std::fs::write(&file, &csv).unwrap();
+        let app = Router::new()
+            .route(
+                "/big.csv",
+                get(move |headers: HeaderMap| async move { super::super::files::serve_file(&file, &headers, false).await }),
+            )
+            .route("/list", get(move || async move { ([(header::CONTENT_TYPE, "text/html")], csv) }))
+            .layer(layer())
+            .layer(axum::middleware::map_response(adjust_for_encoding));
+        let request = |uri: &str, headers: &[(header::HeaderName, &str)]| {
+            let mut request = Request::get(uri).header(header::ACCEPT_ENCODING, "gzip, br");
+            for (name, value) in headers {
+                request = request.header(name, *value);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let full = request("/big.csv", &[]).await.unwrap();
+        assert_eq!(full.status(), 200);
+        assert!(!full.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(full.headers()[header::CONTENT_LENGTH], "8000");
+        let etag = full.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert!(!etag.starts_with("W/"));
+
+        let resume = [(header::RANGE, "bytes=4000-"), (header::IF_RANGE, etag.as_str())];
+        let resumed = request("/big.csv", &resume).await.unwrap();
+        assert_eq!(resumed.status(), 206);
+        assert_eq!(resumed.headers()[header::CONTENT_RANGE], "bytes 4000-7999/8000");
+
+        // Everything else is still compressed
+        let listing = request("/list", &[]).await.unwrap();
+        assert!(listing.headers().contains_key(header::CONTENT_ENCODING));
+    }
+}
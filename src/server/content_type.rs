@@ -0,0 +1,199 @@
+// 🏷️ Content-Type detection and Content-Disposition for served files
+use std::{io::Read, path::Path};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use tokio::io::AsyncReadExt;
+
+/// Bytes read from the start of a file for magic-number sniffing
+const SNIFF_LEN: usize = 8192;
+
+/// RFC 5987 attr-char: everything else in `filename*` is percent-encoded
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// Types that can run script when rendered by the browser
+const ACTIVE_TYPES: &[&str] = &["text/html", "application/xhtml+xml", "image/svg+xml", "text/xml", "application/xml"];
+
+/// MIME type from the extension, falling back to the file's magic bytes
+/// and finally to a UTF-8 check for extensionless text
+pub async fn detect(path: &Path) -> String {
+    if let Some(mime) = from_extension(path) {
+        return mime;
+    }
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    if let Ok(file) = tokio::fs::File::open(path).await {
+        let _ = file.take(SNIFF_LEN as u64).read_to_end(&mut head).await;
+    }
+    sniff(&head)
+}
+
+/// `detect` for code already running on a blocking thread
+pub fn detect_blocking(path: &Path) -> String {
+    if let Some(mime) = from_extension(path) {
+        return mime;
+    }
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    if let Ok(file) = std::fs::File::open(path) {
+        let _ = file.take(SNIFF_LEN as u64).read_to_end(&mut head);
+    }
+    sniff(&head)
+}
+
+fn from_extension(path: &Path) -> Option<String> {
+    mime_guess::from_path(path)
+        .first_raw()
+        .filter(|mime| *mime != "application/octet-stream")
+        .map(|mime| with_charset(mime.to_string()))
+}
+
+fn sniff(head: &[u8]) -> String {
+    let mime = match infer::get(head) {
+        Some(kind) => kind.mime_type().to_string(),
+        None if !head.is_empty() && looks_like_text(head) => "text/plain".to_string(),
+        None => "application/octet-stream".to_string(),
+    };
+    with_charset(mime)
+}
+
+/// MIME type from the extension alone, for content that isn't on disk yet
+pub fn from_name(name: &str) -> String {
+    with_charset(mime_guess::from_path(name).first_or_octet_stream().to_string())
+}
+
+fn with_charset(mime: String) -> String {
+    if mime.starts_with("text/") {
+        format!("{}; charset=utf-8", mime)
+    } else {
+        mime
+    }
+}
+
+fn looks_like_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        // The sniffed window may end in the middle of a character
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// Whether rendering this type inline needs a sandbox (HTML, SVG, XML)
+pub fn is_active(mime: &str) -> bool {
+    let essence = mime.split(';').next().unwrap_or_default().trim();
+    ACTIVE_TYPES.contains(&essence)
+}
+
+/// `?inline=1` (or `inline=true`) asks for a preview instead of a download
+pub fn wants_inline(query: Option<&str>) -> bool {
+    query.is_some_and(|query| {
+        query
+            .split('&')
+            .any(|pair| matches!(pair, "inline" | "inline=1" | "inline=true"))
+    })
+}
+
+/// `attachment`/`inline` with an ASCII `filename` for old clients plus an
+/// RFC 5987 `filename*` whenever the real name isn't plain ASCII
+pub fn content_disposition(inline: bool, name: &str) -> String {
+    let kind = if inline { "inline" } else { "attachment" };
+    let fallback: String = name
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c.is_ascii() && !c.is_ascii_control() => c,
+            _ => '_',
+        })
+        .collect();
+
+    if fallback == name {
+        format!("{}; filename=\"{}\"", kind, name)
+    } else {
+        format!(
+            "{}; filename=\"{}\"; filename*=UTF-8''{}",
+            kind,
+            fallback,
+            utf8_percent_encode(name, ATTR_CHAR)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_awkward_file_names() {
+        assert_eq!(content_disposition(false, "report.pdf"), "attachment; filename=\"report.pdf\"");
+        assert_eq!(content_disposition(true, "a b.txt"), "inline; filename=\"a b.txt\"");
+        assert_eq!(
+            content_disposition(false, "résumé.pdf"),
+            "attachment; filename=\"r_sum_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf"
+        );
+        assert_eq!(
+            content_disposition(false, "say \"hi\".txt"),
+            "attachment; filename=\"say _hi_.txt\"; filename*=UTF-8''say%20%22hi%22.txt"
+        );
+        assert_eq!(
+            content_disposition(false, "back\\slash.txt"),
+            "attachment; filename=\"back_slash.txt\"; filename*=UTF-8''back%5Cslash.txt"
+        );
+    }
+
+    #[test]
+    fn inline_only_when_asked() {
+        for query in ["inline", "inline=1", "inline=true", "x=1&inline"] {
+            assert!(wants_inline(Some(query)), "{}", query);
+        }
+        for query in [None, Some(""), Some("inline=0"), Some("inlined"), Some("x=inline")] {
+            assert!(!wants_inline(query), "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn flags_types_that_can_run_script() {
+        assert!(is_active("text/html; charset=utf-8"));
+        assert!(is_active("image/svg+xml"));
+        assert!(is_active("application/xhtml+xml"));
+        assert!(!is_active("text/plain; charset=utf-8"));
+        assert!(!is_active("image/png"));
+    }
+
+    #[tokio::test]
+    async fn sniffs_files_without_a_known_extension() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = |name: &str, contents: &[u8]| {
+            let path = tmp.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        };
+
+        let png = file("image", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+        let text = file("README", "plain text, ünïcode".as_bytes());
+        let binary = file("blob", &[0, 1, 2, 3, 0xff]);
+        let named = file("data.csv", &[0, 1, 2]);
+        for (path, mime) in [
+            (&png, "image/png"),
+            (&text, "text/plain; charset=utf-8"),
+            (&binary, "application/octet-stream"),
+            (&named, "text/csv; charset=utf-8"),
+        ] {
+            assert_eq!(detect(path).await, mime, "{}", path.display());
+            assert_eq!(detect_blocking(path), mime, "{}", path.display());
+        }
+        // Cut off in the middle of a character is still text
+        assert!(looks_like_text(&"ü".as_bytes()[..1]));
+    }
+}
@@ -0,0 +1,234 @@
+// ⏳ Self-destructing shares: TTL, idle timeout and download limits
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use tokio_util::sync::CancellationToken;
+
+use super::stats::StatsTracker;
+use crate::state::{format_duration, StateHandle};
+
+/// How often the watcher re-evaluates the limits
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Limits given on the command line
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExpiryPolicy {
+    pub expire: Option<Duration>,
+    pub idle_timeout: Option<Duration>,
+    pub max_downloads: Option<u64>,
+}
+
+/// Parse "90s", "30m", "2h", "1d" or combinations like "1h30m"
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("empty duration".to_string());
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return Err(format!("unknown unit '{}' in '{}' (use s, m, h or d)", c, value)),
+        };
+        let amount: u64 = digits
+            .parse()
+            .map_err(|_| format!("missing number before '{}' in '{}'", c, value))?;
+        total = total.saturating_add(amount.saturating_mul(unit));
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err(format!("missing unit after '{}' in '{}' (e.g. 30m)", digits, value));
+    }
+    if total == 0 {
+        return Err("duration must be greater than zero".to_string());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// Live limit tracking for one daemon
+pub struct Expiry {
+    policy: ExpiryPolicy,
+    started: Instant,
+    downloads: AtomicU64,
+}
+
+impl Expiry {
+    pub fn new(policy: ExpiryPolicy) -> Self {
+        Self {
+            policy,
+            started: Instant::now(),
+            downloads: AtomicU64::new(0),
+        }
+    }
+
+    pub fn downloads(&self) -> u64 {
+        self.downloads.load(Ordering::SeqCst).min(self.policy.max_downloads.unwrap_or(u64::MAX))
+    }
+
+    /// Count one download; `false` once the limit has already been used up
+    fn claim_download(&self) -> bool {
+        let previous = self.downloads.fetch_add(1, Ordering::SeqCst);
+        self.policy.max_downloads.is_none_or(|max| previous < max)
+    }
+
+    fn downloads_exhausted(&self) -> bool {
+        self.policy
+            .max_downloads
+            .is_some_and(|max| self.downloads.load(Ordering::SeqCst) >= max)
+    }
+
+    fn ttl_elapsed(&self) -> bool {
+        self.policy.expire.is_some_and(|ttl| self.started.elapsed() >= ttl)
+    }
+
+    /// Reason to shut down now, if any limit has been reached
+    fn check(&self, stats: &StatsTracker) -> Option<String> {
+        if let Some(ttl) = self.policy.expire.filter(|_| self.ttl_elapsed()) {
+            return Some(format!("expired after {}", format_duration(ttl.as_secs())));
+        }
+        let idle = stats.idle_for();
+        if let (Some(timeout), Some(idle)) = (self.policy.idle_timeout, idle) {
+            if idle >= timeout {
+                return Some(format!("idle for {}", format_duration(timeout.as_secs())));
+            }
+        }
+        // Let in-flight downloads finish before pulling the plug
+        if self.downloads_exhausted() && idle.is_some() {
+            return Some(format!(
+                "download limit reached ({})",
+                self.policy.max_downloads.unwrap_or_default()
+            ));
+        }
+        None
+    }
+}
+
+/// Middleware refusing requests once the share has run out, and counting
+/// downloads (see `is_download`)
+pub async fn enforce(State(expiry): State<Arc<Expiry>>, req: Request, next: Next) -> Response {
+    if expiry.ttl_elapsed() || expiry.downloads_exhausted() {
+        return gone();
+    }
+
+    let is_get = req.method() == Method::GET;
+    let response = next.run(req).await;
+
+    if is_get && is_download(&response) && !expiry.claim_download() {
+        return gone();
+    }
+    response
+}
+
+/// Whether a GET response hands out file content from its start: whole files
+/// (attachments and inline previews alike), ranges starting at byte 0 and
+/// multipart ranges. Resuming (`bytes=N-`) doesn't count again.
+fn is_download(response: &Response) -> bool {
+    let headers = response.headers();
+    match response.status() {
+        StatusCode::OK => headers.contains_key(header::CONTENT_DISPOSITION),
+        StatusCode::PARTIAL_CONTENT => {
+            headers
+                .get(header::CONTENT_RANGE)
+                .is_some_and(|v| v.as_bytes().starts_with(b"bytes 0-"))
+                || headers
+                    .get(header::CONTENT_TYPE)
+                    .is_some_and(|v| v.as_bytes().starts_with(b"multipart/byteranges"))
+        }
+        _ => false,
+    }
+}
+
+fn gone() -> Response {
+    Response::builder()
+        .status(StatusCode::GONE)
+        .body(Body::from("This share has expired"))
+        .unwrap()
+}
+
+/// Background task: publishes the download count and triggers shutdown
+/// (recording the reason in the share state) once a limit is hit
+pub async fn watch(
+    expiry: Arc<Expiry>,
+    stats: Arc<StatsTracker>,
+    state: StateHandle,
+    shutdown: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    let mut published = 0;
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+
+        let downloads = expiry.downloads();
+        if downloads != published {
+            state.update(|s| s.downloads = downloads);
+            published = downloads;
+        }
+
+        if let Some(reason) = expiry.check(&stats) {
+            state.update(|s| s.ended_reason = Some(reason));
+            shutdown.cancel();
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: StatusCode, headers: &[(&str, &str)]) -> Response {
+        let mut builder = Response::builder().status(status);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn counts_every_way_of_fetching_a_file_from_the_start() {
+        let attachment = ("content-disposition", "attachment; filename=\"a.bin\"");
+        let inline = ("content-disposition", "inline; filename=\"a.bin\"");
+        assert!(is_download(&response(StatusCode::OK, &[attachment])));
+        assert!(is_download(&response(StatusCode::OK, &[inline])));
+        assert!(is_download(&response(
+            StatusCode::PARTIAL_CONTENT,
+            &[attachment, ("content-range", "bytes 0-99/1000")]
+        )));
+        assert!(is_download(&response(
+            StatusCode::PARTIAL_CONTENT,
+            &[("content-type", "multipart/byteranges; boundary=x")]
+        )));
+
+        // Resumes, listings and revalidations don't
+        assert!(!is_download(&response(
+            StatusCode::PARTIAL_CONTENT,
+            &[attachment, ("content-range", "bytes 500-999/1000")]
+        )));
+        assert!(!is_download(&response(StatusCode::OK, &[("content-type", "text/html")])));
+        assert!(!is_download(&response(StatusCode::NOT_MODIFIED, &[attachment])));
+    }
+}
@@ -0,0 +1,486 @@
+// 📄 File responses shared by single-file and directory mode
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, response::Builder as ResponseBuilder, HeaderMap, StatusCode},
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+use tokio_util::io::ReaderStream;
+
+use super::{
+    checksum,
+    compression::{self, Sidecar},
+    content_type,
+    range::{self, ByteRange, RangeRequest},
+};
+
+/// Boundary separating parts of a multipart/byteranges response
+const BYTERANGES_BOUNDARY: &str = "YEET_BYTERANGES_7f3a9c";
+/// Read buffer size for streamed bodies; memory per download stays at
+/// roughly this much no matter how large the file is
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Set once at daemon start by `--hash-etags`
+static CONTENT_ETAGS: AtomicBool = AtomicBool::new(false);
+
+/// Derive file ETags from the background checksums instead of size+mtime,
+/// so touching a file without changing it keeps client caches valid
+pub fn enable_content_etags() {
+    CONTENT_ETAGS.store(true, Ordering::Relaxed);
+}
+
+/// Validators identifying the current version of a file or listing
+pub struct Validators {
+    pub(super) etag: String,
+    last_modified: Option<SystemTime>,
+}
+
+impl Validators {
+    /// Strong validators from the file's size and modification time
+    pub(super) fn new(metadata: &std::fs::Metadata) -> Self {
+        let mtime = metadata.modified().ok();
+        Self {
+            etag: format!("\"{:x}-{:x}\"", metadata.len(), unix_nanos(mtime)),
+            last_modified: mtime,
+        }
+    }
+
+    /// Like `new`, but tagged by content when `--hash-etags` is on and the
+    /// checksum thread has hashed this version of the file
+    pub(super) fn for_file(path: &Path, metadata: &std::fs::Metadata) -> Self {
+        let validators = Self::new(metadata);
+        if !CONTENT_ETAGS.load(Ordering::Relaxed) {
+            return validators;
+        }
+        validators.with_content_hash(checksum::status(path, metadata))
+    }
+
+    /// Until the hash is ready (or if it never will be) the size+mtime tag stands
+    fn with_content_hash(mut self, hashing: checksum::Hashing) -> Self {
+        if let checksum::Hashing::Ready(checksums) = hashing {
+            let digest = checksums.blake3.as_deref().unwrap_or(&checksums.sha256);
+            self.etag = format!("\"{}\"", &digest[..32]);
+        }
+        self
+    }
+
+    /// Weak validators for a generated directory listing: the page changes
+    /// whenever an entry is added, removed, resized or touched
+    pub fn for_listing<'a>(
+        dir: &std::fs::Metadata,
+        entries: impl IntoIterator<Item = (&'a str, &'a std::fs::Metadata)>,
+    ) -> Self {
+        let mut hasher = DefaultHasher::new();
+        // Pages rendered by a different release look different too
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        let mut last_modified = dir.modified().ok();
+        for (name, metadata) in entries {
+            let mtime = metadata.modified().ok();
+            (name, metadata.len(), metadata.is_file(), unix_nanos(mtime)).hash(&mut hasher);
+            last_modified = last_modified.max(mtime);
+        }
+        Self {
+            etag: format!("W/\"{:x}\"", hasher.finish()),
+            last_modified,
+        }
+    }
+
+    fn last_modified_header(&self) -> Option<String> {
+        self.last_modified.map(http_date)
+    }
+
+    /// Evaluate If-None-Match, or If-Modified-Since when there is no
+    /// If-None-Match (RFC 9110 13.2.2): true when the client's copy is current
+    pub fn is_fresh(&self, headers: &HeaderMap) -> bool {
+        if let Some(value) = headers.get(header::IF_NONE_MATCH) {
+            let Ok(value) = value.to_str() else {
+                return false;
+            };
+            // Weak comparison - W/ prefixes are ignored on both sides
+            let ours = opaque_tag(&self.etag);
+            return value
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || opaque_tag(tag) == ours);
+        }
+
+        let since = headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok());
+        match (since, self.last_modified) {
+            // HTTP dates only carry whole seconds
+            (Some(since), Some(modified)) => DateTime::<Utc>::from(modified).timestamp() <= since.timestamp(),
+            _ => false,
+        }
+    }
+
+    /// Attach ETag, Last-Modified and a revalidate-every-time Cache-Control
+    pub fn apply(&self, mut builder: ResponseBuilder) -> ResponseBuilder {
+        builder = builder
+            .header(header::ETAG, &self.etag)
+            .header(header::CACHE_CONTROL, "no-cache");
+        if let Some(last_modified) = self.last_modified_header() {
+            builder = builder.header(header::LAST_MODIFIED, last_modified);
+        }
+        builder
+    }
+
+    /// 304 for a conditional GET/HEAD whose cached copy is still current
+    pub fn not_modified(&self) -> Response {
+        self.apply(Response::builder())
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    /// Evaluate an If-Range precondition: the range is only honoured when the
+    /// client's validator still matches the file
+    fn if_range_matches(&self, value: &str) -> bool {
+        let value = value.trim();
+        if value.starts_with('"') {
+            // Strong comparison - only exact entity tags match
+            return value == self.etag;
+        }
+        if value.starts_with("W/") {
+            return false;
+        }
+        match (self.last_modified_header(), DateTime::parse_from_rfc2822(value)) {
+            (Some(current), Ok(date)) => http_date(date.into()) == current,
+            _ => false,
+        }
+    }
+}
+
+fn opaque_tag(tag: &str) -> &str {
+    tag.strip_prefix("W/").unwrap_or(tag)
+}
+
+fn unix_nanos(time: Option<SystemTime>) -> u128 {
+    time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+/// Format a timestamp as an IMF-fixdate for HTTP headers
+pub(super) fn http_date(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// Serve a file from disk, honouring conditional (If-None-Match,
+/// If-Modified-Since) and Range/If-Range headers. Files are
+/// downloads (attachment) unless `inline` asks for an in-browser preview.
+pub async fn serve_file(path: &Path, headers: &HeaderMap, inline: bool) -> Response {
+    let metadata = match tokio::fs::metadata(path).await {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return not_found(),
+    };
+    // Precompressed copies stand in for whole-file requests; ranges always
+    // address the raw bytes
+    if !headers.contains_key(header::RANGE) {
+        if let Some(sidecar) = compression::find_sidecar(path, &metadata, headers) {
+            return serve_sidecar(path, sidecar, headers, inline).await;
+        }
+    }
+
+    let len = metadata.len();
+    let validators = Validators::for_file(path, &metadata);
+    if validators.is_fresh(headers) {
+        return validators.not_modified();
+    }
+
+    let ranges = match headers.get(header::RANGE).and_then(|v| v.to_str().ok()) {
+        Some(value) => {
+            let if_range_ok = headers
+                .get(header::IF_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .is_none_or(|v| validators.if_range_matches(v));
+            if if_range_ok {
+                range::parse(value, len)
+            } else {
+                RangeRequest::Full
+            }
+        }
+        None => RangeRequest::Full,
+    };
+
+    let filename = path.file_name().unwrap().to_string_lossy();
+    let mime = content_type::detect(path).await;
+    let disposition = content_type::content_disposition(inline, &filename);
+    let mut builder = validators
+        .apply(Response::builder())
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+    if inline && content_type::is_active(&mime) {
+        // Shared HTML/SVG must not run script on this origin (it holds the admin cookie)
+        builder = builder.header(header::CONTENT_SECURITY_POLICY, "sandbox");
+    }
+    if compression::has_sidecar(path) {
+        builder = builder.header(header::VARY, "accept-encoding");
+    }
+    // Digests are only sent once the background hash is done, never waited for
+    if let Some(checksums) = checksum::lookup(path, &metadata) {
+        builder = checksums.apply(builder);
+    }
+
+    match ranges {
+        RangeRequest::Unsatisfiable => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Body::empty())
+            .unwrap(),
+        RangeRequest::Full => match File::open(path).await {
+            Ok(file) => builder
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, &mime)
+                .header(header::CONTENT_DISPOSITION, &disposition)
+                .header(header::CONTENT_LENGTH, len)
+                .body(Body::from_stream(ReaderStream::with_capacity(file.take(len), CHUNK_SIZE)))
+                .unwrap(),
+            Err(_) => not_found(),
+        },
+        RangeRequest::Partial(ranges) if ranges.len() == 1 => {
+            let range = ranges[0];
+            match open_range(path, range).await {
+                Ok(reader) => builder
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_TYPE, &mime)
+                    .header(header::CONTENT_DISPOSITION, &disposition)
+                    .header(header::CONTENT_RANGE, range.content_range(len))
+                    .header(header::CONTENT_LENGTH, range.len())
+                    .body(Body::from_stream(reader))
+                    .unwrap(),
+                Err(_) => not_found(),
+            }
+        }
+        RangeRequest::Partial(ranges) => {
+            let mut parts = Vec::with_capacity(ranges.len() * 2 + 1);
+            let mut content_length = 0;
+            for range in &ranges {
+                let part_header = Bytes::from(format!(
+                    "\r\n--{}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n",
+                    BYTERANGES_BOUNDARY,
+                    mime,
+                    range.content_range(len)
+                ));
+                content_length += part_header.len() as u64 + range.len();
+                parts.push(stream::once(async move { Ok(part_header) }).boxed());
+                parts.push(lazy_range(path.to_path_buf(), *range).boxed());
+            }
+            let closing = Bytes::from(format!("\r\n--{}--\r\n", BYTERANGES_BOUNDARY));
+            content_length += closing.len() as u64;
+            parts.push(stream::once(async move { Ok(closing) }).boxed());
+
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(
+                    header::CONTENT_TYPE,
+                    format!("multipart/byteranges; boundary={}", BYTERANGES_BOUNDARY),
+                )
+                .header(header::CONTENT_LENGTH, content_length)
+                .body(Body::from_stream(stream::iter(parts).flatten()))
+                .unwrap()
+        }
+    }
+}
+
+/// Serve a precompressed sidecar as the encoded form of `path`: same name and
+/// type, with Content-Encoding set and its own validators
+async fn serve_sidecar(path: &Path, sidecar: Sidecar, headers: &HeaderMap, inline: bool) -> Response {
+    let (metadata, file) = match File::open(&sidecar.path).await {
+        Ok(file) => match file.metadata().await {
+            Ok(metadata) => (metadata, file),
+            Err(_) => return not_found(),
+        },
+        Err(_) => return not_found(),
+    };
+    let validators = Validators::new(&metadata);
+    if validators.is_fresh(headers) {
+        return validators.not_modified();
+    }
+
+    let filename = path.file_name().unwrap().to_string_lossy();
+    let mime = content_type::detect(path).await;
+    let mut builder = validators
+        .apply(Response::builder())
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, &mime)
+        .header(header::CONTENT_DISPOSITION, content_type::content_disposition(inline, &filename))
+        .header(header::CONTENT_ENCODING, sidecar.encoding)
+        .header(header::CONTENT_LENGTH, metadata.len())
+        .header(header::VARY, "accept-encoding")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+    if inline && content_type::is_active(&mime) {
+        builder = builder.header(header::CONTENT_SECURITY_POLICY, "sandbox");
+    }
+    builder
+        .body(Body::from_stream(ReaderStream::with_capacity(file.take(metadata.len()), CHUNK_SIZE)))
+        .unwrap()
+}
+
+/// Open a file positioned at the start of `range`, streaming only its bytes
+async fn open_range(
+    path: &Path,
+    range: ByteRange,
+) -> std::io::Result<ReaderStream<tokio::io::Take<File>>> {
+    let mut file = File::open(path).await?;
+    file.seek(SeekFrom::Start(range.start)).await?;
+    Ok(ReaderStream::with_capacity(file.take(range.len()), CHUNK_SIZE))
+}
+
+/// Like `open_range`, but the file is only opened once the stream is first
+/// polled so multipart responses hold at most one descriptor at a time
+fn lazy_range(
+    path: PathBuf,
+    range: ByteRange,
+) -> impl Stream<Item = std::io::Result<Bytes>> {
+    stream::once(async move { open_range(&path, range).await }).try_flatten()
+}
+
+fn not_found() -> Response {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("File not found"))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use std::{fs, time::Duration};
+
+    fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn evaluates_conditional_requests() {
+        let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let validators = Validators {
+            etag: "\"abc\"".to_string(),
+            last_modified: Some(modified + Duration::from_millis(500)),
+        };
+
+        assert!(!validators.is_fresh(&HeaderMap::new()));
+        assert!(validators.is_fresh(&headers(header::IF_NONE_MATCH, "\"abc\"")));
+        assert!(validators.is_fresh(&headers(header::IF_NONE_MATCH, "\"x\", W/\"abc\"")));
+        assert!(validators.is_fresh(&headers(header::IF_NONE_MATCH, "*")));
+        assert!(!validators.is_fresh(&headers(header::IF_NONE_MATCH, "\"abd\"")));
+
+        let same_second = http_date(modified);
+        let earlier = http_date(modified - Duration::from_secs(1));
+        assert!(validators.is_fresh(&headers(header::IF_MODIFIED_SINCE, &same_second)));
+        assert!(!validators.is_fresh(&headers(header::IF_MODIFIED_SINCE, &earlier)));
+        assert!(!validators.is_fresh(&headers(header::IF_MODIFIED_SINCE, "yesterday")));
+
+        // If-None-Match wins over If-Modified-Since
+        let mut both = headers(header::IF_NONE_MATCH, "\"stale\"");
+        both.insert(header::IF_MODIFIED_SINCE, HeaderValue::from_str(&same_second).unwrap());
+        assert!(!validators.is_fresh(&both));
+    }
+
+    #[test]
+    fn content_tags_wait_for_the_checksum_thread() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("a.txt");
+        fs::write(&path, "hello").unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        let size_and_mtime = Validators::new(&metadata).etag;
+
+        for hashing in [checksum::Hashing::Pending, checksum::Hashing::Unavailable] {
+            assert_eq!(Validators::new(&metadata).with_content_hash(hashing).etag, size_and_mtime);
+        }
+
+        let sha256 = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string();
+        let blake3 = blake3::hash(b"hello").to_hex().to_string();
+        let ready = |blake3: Option<&str>| {
+            checksum::Hashing::Ready(checksum::Checksums {
+                sha256: sha256.clone(),
+                blake3: blake3.map(str::to_string),
+            })
+        };
+        let tagged = Validators::new(&metadata).with_content_hash(ready(None));
+        assert_eq!(tagged.etag, format!("\"{}\"", &sha256[..32]));
+        let tagged = Validators::new(&metadata).with_content_hash(ready(Some(&blake3)));
+        assert_eq!(tagged.etag, format!("\"{}\"", &blake3[..32]));
+        // Strong, and the modification time is still sent
+        assert!(!tagged.etag.starts_with("W/"));
+        assert!(tagged.last_modified.is_some());
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_ranges_and_honours_if_range() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("digits.txt");
+        fs::write(&path, "0123456789").unwrap();
+        let etag = Validators::new(&fs::metadata(&path).unwrap()).etag;
+
+        let response = serve_file(&path, &headers(header::RANGE, "bytes=2-4"), false).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 2-4/10");
+        assert_eq!(body(response).await, "234");
+
+        let response = serve_file(&path, &headers(header::RANGE, "bytes=20-"), false).await;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */10");
+
+        // If-Range with the current tag resumes, a stale one restarts from scratch
+        let mut resume = headers(header::RANGE, "bytes=5-");
+        resume.insert(header::IF_RANGE, HeaderValue::from_str(&etag).unwrap());
+        let response = serve_file(&path, &resume, false).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body(response).await, "56789");
+
+        resume.insert(header::IF_RANGE, HeaderValue::from_static("\"stale\""));
+        let response = serve_file(&path, &resume, false).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, "0123456789");
+    }
+
+    #[tokio::test]
+    async fn sends_multipart_byteranges() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("digits.txt");
+        fs::write(&path, "0123456789").unwrap();
+
+        let response = serve_file(&path, &headers(header::RANGE, "bytes=0-1,7-"), false).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            format!("multipart/byteranges; boundary={}", BYTERANGES_BOUNDARY).as_str()
+        );
+        let length: usize = response.headers()[header::CONTENT_LENGTH].to_str().unwrap().parse().unwrap();
+        let body = body(response).await;
+        assert_eq!(body.len(), length);
+        let expected = format!(
+            "\r\n--{b}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Range: bytes 0-1/10\r\n\r\n01\
+             \r\n--{b}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Range: bytes 7-9/10\r\n\r\n789\
+             \r\n--{b}--\r\n",
+            b = BYTERANGES_BOUNDARY
+        );
+        assert_eq!(body, expected);
+    }
+}
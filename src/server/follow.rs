@@ -0,0 +1,364 @@
+// 📡 Live tail of a growing file (`--follow`): SSE for the browser page, chunked for curl
+use std::{
+    convert::Infallible,
+    io::{self, SeekFrom},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Router,
+};
+use futures_util::{stream, Stream, StreamExt};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+use tokio_util::sync::CancellationToken;
+
+use super::{assets, content_type, files, paths};
+
+/// How much of the existing file a new viewer sees first
+const BACKLOG: u64 = 64 * 1024;
+/// Largest chunk read (and sent) at once
+const CHUNK_SIZE: u64 = 64 * 1024;
+/// How often the file is checked for new data
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+struct Followed {
+    path: PathBuf,
+    name: String,
+    shutdown: CancellationToken,
+}
+
+/// `/` is the live page; `/<name>` is still the plain download, with
+/// `?follow` streaming appended data (`?follow=sse` or an event-stream
+/// Accept header for Server-Sent Events)
+pub fn router<S: Clone + Send + Sync + 'static>(path: PathBuf, shutdown: CancellationToken) -> Router<S> {
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+    let named = format!("/{}", paths::encode_segment(&name));
+    Router::new()
+        .route("/", get(page))
+        .route(&named, get(file))
+        .with_state(Arc::new(Followed { path, name, shutdown }))
+}
+
+async fn file(State(followed): State<Arc<Followed>>, uri: Uri, headers: HeaderMap) -> Response {
+    let follow = uri.query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("follow").filter(|rest| rest.is_empty() || rest.starts_with('=')))
+    });
+    let wants_sse = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/event-stream"));
+
+    match follow {
+        Some("=sse") => sse(&followed).into_response(),
+        Some(_) if wants_sse => sse(&followed).into_response(),
+        Some(_) => chunked(&followed),
+        None => {
+            let inline = content_type::wants_inline(uri.query());
+            files::serve_file(&followed.path, &headers, inline).await
+        }
+    }
+}
+
+/// Server-Sent Events: `data` carries JSON-encoded text (so `\r` progress
+/// bars survive), `reset` means the file was truncated or replaced
+fn sse(followed: &Followed) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut pending = Vec::new();
+    let events = tail(followed.path.clone(), followed.shutdown.clone()).filter_map(move |chunk| {
+        let event = match chunk {
+            Tail::Reset => {
+                pending.clear();
+                Some(Event::default().event("reset").data(""))
+            }
+            Tail::Data(bytes) => {
+                pending.extend_from_slice(&bytes);
+                let text = take_utf8(&mut pending);
+                (!text.is_empty()).then(|| Event::default().json_data(text).unwrap())
+            }
+        };
+        std::future::ready(event.map(Ok))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Raw bytes as they're appended, `tail -f` style (`curl -N <url>?follow`)
+fn chunked(followed: &Followed) -> Response {
+    let body = tail(followed.path.clone(), followed.shutdown.clone()).filter_map(|chunk| {
+        std::future::ready(match chunk {
+            Tail::Data(bytes) => Some(Ok::<_, Infallible>(bytes)),
+            Tail::Reset => None,
+        })
+    });
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        // no-transform keeps the compression layer (and proxies) from buffering it
+        .header(header::CACHE_CONTROL, "no-cache, no-transform")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(Body::from_stream(body))
+        .unwrap()
+}
+
+/// Split off the longest valid UTF-8 prefix, keeping an incomplete trailing
+/// character for the next chunk; invalid bytes become U+FFFD
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let complete = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let rest = pending.split_off(complete);
+    let text = String::from_utf8_lossy(pending).into_owned();
+    *pending = rest;
+    text
+}
+
+enum Tail {
+    Data(Bytes),
+    /// Truncated or rotated: start over from the beginning
+    Reset,
+}
+
+struct TailState {
+    path: PathBuf,
+    file: Option<File>,
+    /// Identity of the open file, to notice it being replaced
+    inode: Option<u64>,
+    offset: u64,
+    /// Skip the partial first line of the backlog
+    trim_first_line: bool,
+    shutdown: CancellationToken,
+}
+
+/// Follow `path`: its last `BACKLOG` bytes, then whatever gets appended.
+/// Ends when the share shuts down.
+fn tail(path: PathBuf, shutdown: CancellationToken) -> impl Stream<Item = Tail> {
+    let state = TailState {
+        path,
+        file: None,
+        inode: None,
+        offset: 0,
+        trim_first_line: false,
+        shutdown,
+    };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            // Errors are usually a rotation in progress; try again next time
+            if let Ok(Some(chunk)) = state.poll().await {
+                return Some((chunk, state));
+            }
+            tokio::select! {
+                _ = state.shutdown.cancelled() => return None,
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    })
+}
+
+impl TailState {
+    /// One look at the file: new data, a reset, or nothing yet
+    async fn poll(&mut self) -> io::Result<Option<Tail>> {
+        let metadata = tokio::fs::metadata(&self.path).await?;
+        let inode = file_id(&metadata);
+
+        if self.file.is_none() {
+            self.file = Some(File::open(&self.path).await?);
+            self.inode = inode;
+            self.offset = metadata.len().saturating_sub(BACKLOG);
+            self.trim_first_line = self.offset > 0;
+        } else if inode != self.inode || metadata.len() < self.offset {
+            self.file = Some(File::open(&self.path).await?);
+            self.inode = inode;
+            self.offset = 0;
+            self.trim_first_line = false;
+            return Ok(Some(Tail::Reset));
+        }
+
+        if metadata.len() <= self.offset {
+            return Ok(None);
+        }
+        let want = (metadata.len() - self.offset).min(CHUNK_SIZE) as usize;
+        let file = self.file.as_mut().unwrap();
+        file.seek(SeekFrom::Start(self.offset)).await?;
+        let mut buf = vec![0; want];
+        let read = file.read(&mut buf).await?;
+        buf.truncate(read);
+        self.offset += read as u64;
+
+        if std::mem::take(&mut self.trim_first_line) {
+            if let Some(newline) = buf.iter().position(|&b| b == b'\n') {
+                buf.drain(..=newline);
+            }
+        }
+        Ok((!buf.is_empty()).then(|| Tail::Data(Bytes::from(buf))))
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+async fn page(State(followed): State<Arc<Followed>>) -> Response {
+    let name = html_escape(&followed.name);
+    let link = html_escape(&paths::encode_segment(&followed.name));
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::CONTENT_SECURITY_POLICY, assets::CSP)
+        .body(Body::from(live_page(&name, &link)))
+        .unwrap()
+}
+
+/// Retro terminal view; text is only ever inserted with textContent
+fn live_page(name: &str, link: &str) -> String {
+    let css = assets::url("yeet.css");
+    let js = assets::url("follow.js");
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>YEET // LIVE // {name}</title>
+    <link href="{css}" rel="stylesheet">
+    <style>
+        * {{ margin: 0; padding: 0; box-sizing: border-box; }}
+        body {{
+            background: #0a0e27;
+            color: #00ff9f;
+            font-family: 'DejaVu Sans Mono', monospace;
+            height: 100vh;
+            display: flex;
+            flex-direction: column;
+            padding: 1rem;
+            gap: 1rem;
+        }}
+        .header {{
+            border: 2px solid #00ff9f;
+            padding: 1rem 1.5rem;
+            background: rgba(0, 255, 159, 0.05);
+            display: flex;
+            align-items: center;
+            gap: 1rem;
+            flex-wrap: wrap;
+        }}
+        .logo {{
+            font-size: 1.5rem;
+            font-weight: bold;
+            color: #00d4ff;
+            text-shadow: 0 0 10px #00d4ff;
+        }}
+        .subtitle {{ color: #ff00ff; flex: 1; word-break: break-all; }}
+        .status {{ font-size: 0.85rem; }}
+        .status.live {{ color: #00ff9f; text-shadow: 0 0 5px #00ff9f; }}
+        .status.down {{ color: #ff00ff; }}
+        a, button {{
+            border: 1px solid #ff00ff;
+            color: #ff00ff;
+            background: none;
+            padding: 0.4rem 0.8rem;
+            font-family: inherit;
+            font-size: 0.85rem;
+            text-decoration: none;
+            cursor: pointer;
+        }}
+        a:hover, button:hover {{ background: rgba(255, 0, 255, 0.1); }}
+        pre {{
+            flex: 1;
+            overflow: auto;
+            border: 2px solid #00ff9f;
+            padding: 1rem;
+            font-size: 0.85rem;
+            line-height: 1.4;
+            white-space: pre-wrap;
+            word-break: break-all;
+        }}
+    </style>
+</head>
+<body data-link="{link}">
+    <div class="header">
+        <div class="logo">█ YEET.SH █</div>
+        <div class="subtitle">// LIVE // {name}</div>
+        <span id="status" class="status down">○ CONNECTING</span>
+        <button id="pause">⏸ PAUSE SCROLL</button>
+        <a href="{link}">⬇ SNAPSHOT</a>
+    </div>
+    <pre id="log"></pre>
+
+    <script src="{js}"></script>
+</body>
+</html>"#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    async fn next(events: &mut (impl Stream<Item = Tail> + Unpin)) -> Option<Tail> {
+        tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap()
+    }
+
+    #[test]
+    fn keeps_split_characters_for_the_next_chunk() {
+        let mut pending = "héllo".as_bytes()[..2].to_vec();
+        assert_eq!(take_utf8(&mut pending), "h");
+        assert_eq!(pending, [0xc3]);
+        pending.extend_from_slice(&"héllo".as_bytes()[2..]);
+        assert_eq!(take_utf8(&mut pending), "éllo");
+        assert!(pending.is_empty());
+
+        let mut invalid = vec![b'a', 0xff, b'b'];
+        assert_eq!(take_utf8(&mut invalid), "a\u{fffd}b");
+    }
+
+    #[tokio::test]
+    async fn tails_appends_and_truncation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("train.log");
+        std::fs::write(&path, "epoch 1\n").unwrap();
+        let shutdown = CancellationToken::new();
+        let mut events = Box::pin(tail(path.clone(), shutdown.clone()));
+
+        assert!(matches!(next(&mut events).await, Some(Tail::Data(b)) if b == "epoch 1\n"));
+
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"epoch 2\n").unwrap();
+        assert!(matches!(next(&mut events).await, Some(Tail::Data(b)) if b == "epoch 2\n"));
+
+        std::fs::write(&path, "new\n").unwrap();
+        assert!(matches!(next(&mut events).await, Some(Tail::Reset)));
+        assert!(matches!(next(&mut events).await, Some(Tail::Data(b)) if b == "new\n"));
+
+        shutdown.cancel();
+        assert!(next(&mut events).await.is_none());
+    }
+}
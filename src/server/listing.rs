@@ -0,0 +1,662 @@
+// 📂 Directory listing page. Entries reach the browser as a JSON data island,
+// so file names are never spliced into markup or script.
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    fs::{self, Metadata},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::Response,
+    routing::get,
+    Router,
+};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{
+    archive::{self, ArchiveSink},
+    assets,
+    checksum::{self, Hashing},
+    content_type,
+    files::Validators,
+    paths::{self, PathError, SafeRoot},
+};
+use crate::shared::{format_bytes, DirEntry, DirListing, EntryKind};
+
+/// Page size of JSON listings unless `limit` says otherwise
+const DEFAULT_LIMIT: usize = 1000;
+const MAX_LIMIT: usize = 10_000;
+
+/// One row of the listing as the page script sees it
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    /// Display name (non-UTF-8 bytes shown as U+FFFD)
+    pub name: String,
+    /// Absolute, percent-encoded link built from the raw name bytes
+    pub path: String,
+    pub size: String,
+    pub size_bytes: u64,
+    pub icon: &'static str,
+    pub is_file: bool,
+    pub sha256: Option<String>,
+    #[serde(skip)]
+    pub metadata: Metadata,
+}
+
+#[derive(Serialize)]
+struct PageData<'a> {
+    files: &'a [Entry],
+    checksums: bool,
+}
+
+/// Entries of `dir` (requested as `request_path`) permitted under `root`,
+/// sorted by name. The flag is true while some checksums are still pending.
+pub fn read_entries(dir: &Path, request_path: &str, root: &SafeRoot) -> io::Result<(Vec<Entry>, bool)> {
+    let mut raw: Vec<(OsString, Metadata)> = fs::read_dir(dir)?
+        .flatten()
+        .filter(|entry| root.permits(&entry.path()))
+        .filter_map(|entry| Some((entry.file_name(), fs::metadata(entry.path()).ok()?)))
+        .collect();
+    raw.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let base = request_path.trim_end_matches('/');
+    let mut hashing = false;
+    let entries = raw
+        .into_iter()
+        .map(|(name, metadata)| {
+            let is_file = metadata.is_file();
+            let sha256 = if is_file {
+                match checksum::status(&dir.join(&name), &metadata) {
+                    Hashing::Ready(checksums) => Some(checksums.sha256),
+                    Hashing::Pending => {
+                        hashing = true;
+                        None
+                    }
+                    Hashing::Unavailable => None,
+                }
+            } else {
+                None
+            };
+            Entry {
+                path: format!("{}/{}", base, paths::encode_os_segment(&name)),
+                name: name.to_string_lossy().into_owned(),
+                size: if is_file { format_bytes(metadata.len()) } else { "-".to_string() },
+                size_bytes: metadata.len(),
+                icon: if is_file { "📄" } else { "📁" },
+                is_file,
+                sha256,
+                metadata,
+            }
+        })
+        .collect();
+    Ok((entries, hashing))
+}
+
+/// The listing page for `dir`, or a 304 when the client's copy is current
+pub fn response(dir: &Path, request_path: &str, root: &SafeRoot, headers: &HeaderMap) -> Response {
+    let (Ok(dir_metadata), Ok((entries, hashing))) = (fs::metadata(dir), read_entries(dir, request_path, root)) else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("Not found"))
+            .unwrap();
+    };
+
+    // Unchanged listings revalidate with a 304 instead of re-sending the page,
+    // but not while checksums are still filling in
+    let validators = Validators::for_listing(
+        &dir_metadata,
+        entries.iter().map(|entry| (entry.name.as_str(), &entry.metadata)),
+    );
+    if !hashing && validators.is_fresh(headers) {
+        return validators.not_modified();
+    }
+
+    let title = dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let builder = if hashing { Response::builder() } else { validators.apply(Response::builder()) };
+    builder
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::VARY, "accept")
+        .header(header::CONTENT_SECURITY_POLICY, assets::CSP)
+        .body(Body::from(page(&title, &entries)))
+        .unwrap()
+}
+
+/// Options of a JSON listing: `?path=sub/dir&recursive=1&offset=0&limit=100&hashes=1`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ListQuery {
+    /// Directory to list, from the share root (`/api/ls` only)
+    path: String,
+    #[serde(deserialize_with = "flag")]
+    recursive: bool,
+    offset: usize,
+    limit: Option<usize>,
+    /// Include checksums that are ready (and queue the rest)
+    #[serde(deserialize_with = "flag")]
+    hashes: bool,
+}
+
+impl ListQuery {
+    /// Options given on a directory URL requested with `Accept: application/json`
+    pub fn from_uri(uri: &Uri) -> Self {
+        Query::try_from_uri(uri).map(|Query(query)| query).unwrap_or_default()
+    }
+}
+
+/// `1`, `true`, `yes` or a bare key count as set
+fn flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Ok(matches!(value.as_str(), "" | "1" | "true" | "yes"))
+}
+
+/// Whether a directory request asked for JSON rather than the page
+pub fn wants_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.split(',').any(|t| t.trim().starts_with("application/json")))
+}
+
+/// `/api/ls?path=` for directory shares
+pub fn api_router<S: Clone + Send + Sync + 'static>(root: SafeRoot) -> Router<S> {
+    Router::new()
+        .route("/api/ls", get(api_ls))
+        .with_state(Arc::new(root))
+}
+
+async fn api_ls(State(root): State<Arc<SafeRoot>>, Query(query): Query<ListQuery>) -> Response {
+    // The query path is plain text; re-encode it so `%` in names survives resolving
+    let uri_path: String = query
+        .path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", paths::encode_segment(segment)))
+        .collect();
+    let dir = match root.resolve(&uri_path) {
+        Ok(dir) if dir.is_dir() => dir,
+        Ok(_) => return plain(StatusCode::BAD_REQUEST, "Not a directory"),
+        Err(PathError::Forbidden) => return plain(StatusCode::FORBIDDEN, "Forbidden"),
+        Err(PathError::NotFound) => return plain(StatusCode::NOT_FOUND, "Not found"),
+    };
+    json_response(dir, uri_path, (*root).clone(), query).await
+}
+
+/// One page of the listing of `dir` (requested as `uri_path`) as JSON
+pub async fn json_response(dir: PathBuf, uri_path: String, root: SafeRoot, query: ListQuery) -> Response {
+    let listing = tokio::task::spawn_blocking(move || list_json(&dir, &uri_path, &root, &query)).await;
+    match listing {
+        Ok(Ok(listing)) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .header(header::VARY, "accept")
+            .body(Body::from(serde_json::to_string(&listing).unwrap_or_default()))
+            .unwrap(),
+        _ => plain(StatusCode::NOT_FOUND, "Not found"),
+    }
+}
+
+/// Something found while listing, relative to the listed directory
+pub(super) struct Found {
+    pub relative: PathBuf,
+    pub path: PathBuf,
+    pub metadata: Metadata,
+}
+
+/// The listed directory, as URL path and as display path from the share root
+pub(super) struct Base {
+    href: String,
+    path: String,
+}
+
+impl Base {
+    pub fn new(uri_path: &str) -> Self {
+        let href = uri_path.trim_end_matches('/').to_string();
+        let path = percent_decode_str(&href).decode_utf8_lossy().trim_matches('/').to_string();
+        Self { href, path }
+    }
+
+    /// JSON entry (without checksums) for something found below this directory
+    pub fn entry(&self, found: &Found) -> DirEntry {
+        let is_file = found.metadata.is_file();
+        let relative = found.relative.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/");
+        let encoded: Vec<String> = found.relative.iter().map(paths::encode_os_segment).collect();
+        DirEntry {
+            name: found.path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            path: if self.path.is_empty() { relative } else { format!("{}/{}", self.path, relative) },
+            href: format!("{}/{}", self.href, encoded.join("/")),
+            kind: if is_file { EntryKind::File } else { EntryKind::Dir },
+            size: if is_file { found.metadata.len() } else { 0 },
+            mtime: found
+                .metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+            mime: is_file.then(|| content_type::detect_blocking(&found.path)),
+            sha256: None,
+            blake3: None,
+        }
+    }
+}
+
+fn list_json(dir: &Path, uri_path: &str, root: &SafeRoot, query: &ListQuery) -> io::Result<DirListing> {
+    let mut found = if query.recursive {
+        walk_tree(dir, root)?
+    } else {
+        fs::read_dir(dir)?
+            .flatten()
+            .filter(|entry| root.permits(&entry.path()))
+            .filter_map(|entry| {
+                Some(Found {
+                    relative: PathBuf::from(entry.file_name()),
+                    metadata: fs::metadata(entry.path()).ok()?,
+                    path: entry.path(),
+                })
+            })
+            .collect()
+    };
+    found.sort_by(|a, b| a.relative.cmp(&b.relative));
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let total = found.len();
+    let offset = query.offset.min(total);
+    let end = (offset + limit).min(total);
+
+    let base = Base::new(uri_path);
+    let mut hashes_pending = 0;
+
+    let entries = found
+        .drain(offset..end)
+        .map(|found| {
+            let mut entry = base.entry(&found);
+            match (query.hashes && found.metadata.is_file()).then(|| checksum::status(&found.path, &found.metadata)) {
+                Some(Hashing::Ready(checksums)) => {
+                    entry.sha256 = Some(checksums.sha256);
+                    entry.blake3 = checksums.blake3;
+                }
+                Some(Hashing::Pending) => hashes_pending += 1,
+                _ => {}
+            }
+            entry
+        })
+        .collect();
+
+    Ok(DirListing {
+        path: format!("/{}", base.path),
+        recursive: query.recursive,
+        total,
+        offset,
+        limit,
+        next_offset: (end < total).then_some(end),
+        hashes_pending,
+        entries,
+    })
+}
+
+/// Everything below `dir`, under the same symlink rules (and loop guard) as archives
+pub(super) fn walk_tree(dir: &Path, root: &SafeRoot) -> io::Result<Vec<Found>> {
+    struct Tree<'a> {
+        dir: &'a Path,
+        found: Vec<Found>,
+    }
+
+    impl ArchiveSink for Tree<'_> {
+        fn add_dir(&mut self, name: &str, path: &Path, metadata: &Metadata) -> io::Result<()> {
+            self.add_file(name, path, metadata)
+        }
+
+        fn add_file(&mut self, _name: &str, path: &Path, metadata: &Metadata) -> io::Result<()> {
+            if let Ok(relative) = path.strip_prefix(self.dir) {
+                if !relative.as_os_str().is_empty() {
+                    self.found.push(Found {
+                        relative: relative.to_path_buf(),
+                        path: path.to_path_buf(),
+                        metadata: metadata.clone(),
+                    });
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let mut tree = Tree { dir, found: Vec::new() };
+    archive::walk(dir, "", root, &mut HashSet::new(), &mut tree)?;
+    Ok(tree.found)
+}
+
+fn plain(status: StatusCode, message: &'static str) -> Response {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(message))
+        .unwrap()
+}
+
+fn page(title: &str, entries: &[Entry]) -> String {
+    let data = PageData {
+        files: entries,
+        checksums: checksum::enabled(),
+    };
+    let title = html_escape(title);
+    let data = script_safe_json(&data);
+    let css = assets::url("yeet.css");
+    let js = assets::url("listing.js");
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>YEET // {title}</title>
+    <link href="{css}" rel="stylesheet">
+    <style>
+        * {{ margin: 0; padding: 0; box-sizing: border-box; }}
+        body {{
+            background: #0a0e27;
+            color: #00ff9f;
+            font-family: 'DejaVu Sans Mono', monospace;
+            padding: 2rem;
+            min-height: 100vh;
+        }}
+        .container {{ max-width: 1200px; margin: 0 auto; }}
+        .header {{
+            border: 2px solid #00ff9f;
+            padding: 1.5rem;
+            margin-bottom: 2rem;
+            background: rgba(0, 255, 159, 0.05);
+        }}
+        .logo {{
+            font-size: 2rem;
+            font-weight: bold;
+            color: #00d4ff;
+            text-shadow: 0 0 10px #00d4ff;
+            margin-bottom: 0.5rem;
+        }}
+        .subtitle {{
+            color: #ff00ff;
+            font-size: 0.9rem;
+        }}
+        .controls {{
+            display: flex;
+            gap: 1rem;
+            margin-bottom: 1rem;
+            flex-wrap: wrap;
+        }}
+        input, select {{
+            background: #1a1f3a;
+            border: 1px solid #00ff9f;
+            color: #00ff9f;
+            padding: 0.5rem 1rem;
+            font-family: 'DejaVu Sans Mono', monospace;
+            font-size: 0.9rem;
+        }}
+        input:focus, select:focus {{
+            outline: none;
+            box-shadow: 0 0 10px rgba(0, 255, 159, 0.5);
+        }}
+        table {{
+            width: 100%;
+            border-collapse: collapse;
+            border: 2px solid #00ff9f;
+        }}
+        th {{
+            background: rgba(0, 255, 159, 0.1);
+            padding: 1rem;
+            text-align: left;
+            border-bottom: 2px solid #00ff9f;
+            cursor: pointer;
+            user-select: none;
+            color: #00d4ff;
+        }}
+        th:hover {{
+            background: rgba(0, 255, 159, 0.2);
+        }}
+        td {{
+            padding: 0.75rem 1rem;
+            border-bottom: 1px solid rgba(0, 255, 159, 0.2);
+        }}
+        tr:hover {{
+            background: rgba(0, 255, 159, 0.05);
+        }}
+        a {{
+            color: #00ff9f;
+            text-decoration: none;
+            display: flex;
+            align-items: center;
+            gap: 0.5rem;
+        }}
+        a:hover {{
+            color: #00d4ff;
+            text-shadow: 0 0 5px #00d4ff;
+        }}
+        .icon {{ font-size: 1.2rem; }}
+        .download-btn {{
+            border: 1px solid #ff00ff;
+            color: #ff00ff;
+            padding: 0.5rem 1rem;
+            font-size: 0.9rem;
+        }}
+        .download-btn:hover {{
+            background: rgba(255, 0, 255, 0.1);
+            color: #ff00ff;
+            text-shadow: 0 0 5px #ff00ff;
+        }}
+        .stats {{
+            margin-top: 1rem;
+            padding: 1rem;
+            border: 1px solid rgba(255, 0, 255, 0.3);
+            background: rgba(255, 0, 255, 0.05);
+            color: #ff00ff;
+            font-size: 0.85rem;
+        }}
+        .hash {{
+            font-size: 0.8rem;
+            color: #00d4ff;
+            cursor: copy;
+        }}
+        .hash.pending {{
+            color: rgba(0, 255, 159, 0.4);
+            cursor: default;
+        }}
+        .snippet {{
+            display: block;
+            font-size: 0.8rem;
+            color: rgba(0, 255, 159, 0.6);
+            white-space: pre-wrap;
+            word-break: break-all;
+        }}
+        .sort-indicator {{
+            font-size: 0.7rem;
+            margin-left: 0.5rem;
+            color: #ff00ff;
+        }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <div class="logo">█ YEET.SH █</div>
+            <div class="subtitle">// {title}</div>
+        </div>
+
+        <div class="controls">
+            <input
+                type="text"
+                id="search"
+                placeholder="⚡ SEARCH FILES..."
+                style="flex: 1; min-width: 200px;">
+            <select id="scope">
+                <option value="here">THIS FOLDER</option>
+                <option value="all">WHOLE SHARE</option>
+            </select>
+            <input
+                type="text"
+                id="grep"
+                hidden
+                placeholder="🔍 CONTAINING TEXT..."
+                style="min-width: 180px;">
+            <select id="filter">
+                <option value="all">ALL FILES</option>
+                <option value="files">FILES ONLY</option>
+                <option value="dirs">DIRS ONLY</option>
+            </select>
+            <a class="download-btn" href="?download=zip">⬇ ZIP</a>
+            <a class="download-btn" href="?download=tar.gz">⬇ TAR.GZ</a>
+            <a class="download-btn" href="/SHA256SUMS" id="checksums" hidden>⬇ SHA256SUMS</a>
+        </div>
+
+        <table>
+            <thead>
+                <tr>
+                    <th data-sort="name" style="width: 45%">
+                        NAME
+                        <span class="sort-indicator"></span>
+                    </th>
+                    <th data-sort="size" style="width: 20%">
+                        SIZE
+                        <span class="sort-indicator"></span>
+                    </th>
+                    <th style="width: 20%">SHA-256</th>
+                    <th style="width: 15%">TYPE</th>
+                </tr>
+            </thead>
+            <tbody id="rows"></tbody>
+        </table>
+
+        <div class="stats">
+            <span id="stats"></span>
+        </div>
+    </div>
+
+    <script type="application/json" id="listing-data">{data}</script>
+    <script src="{js}"></script>
+</body>
+</html>"#)
+}
+
+/// JSON that can sit inside a `<script>` element: `<`, `>` and `&` are
+/// escaped so no name can close the element or open a comment
+fn script_safe_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value)
+        .unwrap_or_else(|_| "null".to_string())
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::paths::SymlinkPolicy;
+
+    /// Names can't contain `/`, so `</script>` itself is covered below
+    const HOSTILE: &[&str] = &[
+        "<script>alert(1)<script>",
+        "it's \\ \"quoted\"",
+        "line\nbreak",
+        "<!-- comment",
+        "\u{2028}separator",
+    ];
+
+    fn island(html: &str) -> &str {
+        let start = html.find(r#"id="listing-data">"#).unwrap() + r#"id="listing-data">"#.len();
+        let end = start + html[start..].find("</script>").unwrap();
+        &html[start..end]
+    }
+
+    #[test]
+    fn hostile_names_stay_inside_the_data_island() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("<b>share");
+        fs::create_dir(&dir).unwrap();
+        for name in HOSTILE {
+            fs::write(dir.join(name), "x").unwrap();
+        }
+        let root = SafeRoot::new(&dir, SymlinkPolicy::Deny).unwrap();
+        let (entries, _) = read_entries(&dir, "/", &root).unwrap();
+        let html = page("<b>share", &entries);
+
+        let closes = |html: &str| html.matches("</script>").count();
+        assert_eq!(closes(&html), closes(&page("empty", &[])));
+        assert!(!html.contains("<b>share"));
+        assert!(!html.contains("<script>alert"));
+        assert!(!html.contains("<!--"));
+
+        let parsed: serde_json::Value = serde_json::from_str(island(&html)).unwrap();
+        let mut names: Vec<&str> = parsed["files"].as_array().unwrap().iter().map(|f| f["name"].as_str().unwrap()).collect();
+        names.sort();
+        let mut expected = HOSTILE.to_vec();
+        expected.sort();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn escapes_script_terminators() {
+        let json = script_safe_json(&["</script><!--", "a&b"]);
+        assert_eq!(json, r#"["\u003c/script\u003e\u003c!--","a\u0026b"]"#);
+        assert_eq!(serde_json::from_str::<Vec<String>>(&json).unwrap(), ["</script><!--", "a&b"]);
+    }
+
+    #[test]
+    fn pages_through_json_listings() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("sub/deep")).unwrap();
+        fs::write(tmp.path().join("a 1.txt"), "a").unwrap();
+        fs::write(tmp.path().join("sub/b.csv"), "bb").unwrap();
+        fs::write(tmp.path().join("sub/deep/c%.json"), "{}").unwrap();
+        let root = SafeRoot::new(tmp.path(), SymlinkPolicy::Deny).unwrap();
+        let sub = tmp.path().join("sub");
+
+        let flat = list_json(&sub, "/sub", &root, &ListQuery::default()).unwrap();
+        let names: Vec<_> = flat.entries.iter().map(|e| (e.path.as_str(), e.kind)).collect();
+        assert_eq!(names, [("sub/b.csv", EntryKind::File), ("sub/deep", EntryKind::Dir)]);
+        assert_eq!(flat.entries[0].size, 2);
+        assert_eq!(flat.entries[0].mime.as_deref(), Some("text/csv; charset=utf-8"));
+
+        let query = ListQuery { recursive: true, limit: Some(2), ..Default::default() };
+        let first = list_json(tmp.path(), "/", &root, &query).unwrap();
+        assert_eq!((first.total, first.next_offset), (5, Some(2)));
+        assert_eq!(first.entries[0].href, "/a%201.txt");
+
+        let query = ListQuery { offset: 4, ..query };
+        let last = list_json(tmp.path(), "/", &root, &query).unwrap();
+        assert_eq!(last.next_offset, None);
+        assert_eq!(last.entries[0].path, "sub/deep/c%.json");
+        assert_eq!(last.entries[0].href, "/sub/deep/c%25.json");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lists_non_utf8_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join(std::ffi::OsStr::from_bytes(b"caf\xe9.txt")), "x").unwrap();
+        let root = SafeRoot::new(tmp.path(), SymlinkPolicy::Deny).unwrap();
+        let (entries, _) = read_entries(tmp.path(), "/sub/", &root).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "caf\u{fffd}.txt");
+        // The link carries the original bytes, so it resolves back to the file
+        assert_eq!(entries[0].path, "/sub/caf%E9.txt");
+        assert!(root.resolve("/caf%E9.txt").unwrap().is_file());
+    }
+}
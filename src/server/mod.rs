@@ -0,0 +1,20 @@
+// 🌐 Daemon HTTP server building blocks (middleware, accounting, file serving, compression, uploads, WebDAV, proxying, stdin, embedded UI assets)
+pub mod stats;
+pub mod files;
+pub mod content_type;
+pub mod compression;
+pub mod range;
+pub mod paths;
+pub mod archive;
+pub mod auth;
+pub mod expiry;
+pub mod upload;
+pub mod tus;
+pub mod webdav;
+pub mod proxy;
+pub mod stdin;
+pub mod follow;
+pub mod checksum;
+pub mod listing;
+pub mod search;
+pub mod assets;
@@ -0,0 +1,293 @@
+// 🛡️ Resolve request paths against the shared root without escaping it
+use std::{
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+};
+
+use percent_encoding::{percent_decode_str, percent_encode, utf8_percent_encode, AsciiSet, CONTROLS};
+
+/// Characters escaped when a file name is placed in a URL path segment
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// How symlinks inside a shared directory are treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SymlinkPolicy {
+    /// Never serve anything reached through a symlink
+    Deny,
+    /// Follow symlinks as long as the target stays inside the shared root
+    #[default]
+    WithinRoot,
+    /// Follow every symlink, wherever it points
+    FollowAll,
+}
+
+/// Why a request path could not be resolved
+#[derive(Debug, PartialEq, Eq)]
+pub enum PathError {
+    /// Nothing exists at the resolved location (404)
+    NotFound,
+    /// Traversal or symlink escape attempt (403)
+    Forbidden,
+}
+
+/// Canonical shared root plus the symlink policy applied beneath it
+#[derive(Debug, Clone)]
+pub struct SafeRoot {
+    root: PathBuf,
+    policy: SymlinkPolicy,
+}
+
+impl SafeRoot {
+    pub fn new(root: &Path, policy: SymlinkPolicy) -> io::Result<Self> {
+        Ok(Self {
+            root: root.canonicalize()?,
+            policy,
+        })
+    }
+
+    /// The canonical shared directory
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Map a raw (percent-encoded) URI path onto the filesystem.
+    ///
+    /// Every segment is decoded and checked individually: `..` and NUL bytes
+    /// are rejected outright (as are backslash-separated tricks on Windows,
+    /// while elsewhere a backslash is just part of a name), and each symlink
+    /// met along the way is checked against the policy.
+    pub fn resolve(&self, uri_path: &str) -> Result<PathBuf, PathError> {
+        let decoded: Vec<u8> = percent_decode_str(uri_path).collect();
+        if decoded.contains(&0) {
+            return Err(PathError::Forbidden);
+        }
+
+        let mut current = self.root.clone();
+        // Once a follow-all symlink leaves the root, containment no longer applies
+        let mut escaped = false;
+
+        for segment in decoded.split(|&b| is_separator(b)) {
+            match segment {
+                b"" | b"." => continue,
+                b".." => return Err(PathError::Forbidden),
+                _ => {}
+            }
+            let segment = bytes_to_os_str(segment).ok_or(PathError::Forbidden)?;
+            let candidate = current.join(segment);
+
+            let metadata = std::fs::symlink_metadata(&candidate).map_err(|_| PathError::NotFound)?;
+            current = if metadata.file_type().is_symlink() {
+                let target = self.follow(&candidate)?;
+                escaped |= !target.starts_with(&self.root);
+                target
+            } else {
+                candidate
+            };
+        }
+
+        if !escaped && !current.starts_with(&self.root) {
+            return Err(PathError::Forbidden);
+        }
+        Ok(current)
+    }
+
+    /// Resolve everything but the last segment, which is returned as a plain
+    /// child of its (resolved) parent without being followed. Used for paths
+    /// that are about to be created, replaced, moved or deleted.
+    pub fn resolve_entry(&self, uri_path: &str) -> Result<PathBuf, PathError> {
+        let trimmed = uri_path.trim_end_matches('/');
+        let (parent, name) = trimmed.rsplit_once('/').unwrap_or(("", trimmed));
+
+        let name: Vec<u8> = percent_decode_str(name).collect();
+        if matches!(name.as_slice(), b"" | b"." | b"..") || name.iter().any(|&b| b == 0 || is_separator(b)) {
+            return Err(PathError::Forbidden);
+        }
+        let name = bytes_to_os_str(&name).ok_or(PathError::Forbidden)?;
+
+        let entry = self.resolve(parent)?.join(name);
+        if std::fs::symlink_metadata(&entry).is_ok() && !self.permits(&entry) {
+            return Err(PathError::Forbidden);
+        }
+        Ok(entry)
+    }
+
+    /// Whether a directory entry may be shown in listings under the policy
+    pub fn permits(&self, entry: &Path) -> bool {
+        match std::fs::symlink_metadata(entry) {
+            Ok(metadata) if metadata.file_type().is_symlink() => self.follow(entry).is_ok(),
+            Ok(_) => true,
+            Err(_) => false,
+        }
+    }
+
+    fn follow(&self, link: &Path) -> Result<PathBuf, PathError> {
+        if self.policy == SymlinkPolicy::Deny {
+            return Err(PathError::Forbidden);
+        }
+        let target = link.canonicalize().map_err(|_| PathError::NotFound)?;
+        if self.policy == SymlinkPolicy::WithinRoot && !target.starts_with(&self.root) {
+            return Err(PathError::Forbidden);
+        }
+        Ok(target)
+    }
+}
+
+/// Path separators of the host: only Windows treats `\` as one
+fn is_separator(byte: u8) -> bool {
+    byte == b'/' || (cfg!(windows) && byte == b'\\')
+}
+
+/// Percent-encode a file name for use as a single URL path segment
+pub fn encode_segment(name: &str) -> String {
+    utf8_percent_encode(name, SEGMENT).to_string()
+}
+
+/// Like `encode_segment`, but for raw file names that may not be UTF-8
+#[cfg(unix)]
+pub fn encode_os_segment(name: &OsStr) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    percent_encode(name.as_bytes(), SEGMENT).to_string()
+}
+
+#[cfg(not(unix))]
+pub fn encode_os_segment(name: &OsStr) -> String {
+    encode_segment(&name.to_string_lossy())
+}
+
+#[cfg(unix)]
+fn bytes_to_os_str(bytes: &[u8]) -> Option<&OsStr> {
+    use std::os::unix::ffi::OsStrExt;
+    Some(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn bytes_to_os_str(bytes: &[u8]) -> Option<&OsStr> {
+    std::str::from_utf8(bytes).ok().map(OsStr::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Layout: <tmp>/outside/secret.txt, <tmp>/root/{a.txt, sub/b.txt}
+    fn fixture() -> (tempfile::TempDir, PathBuf) {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("root");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::create_dir_all(tmp.path().join("outside")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("sub/b.txt"), "b").unwrap();
+        fs::write(tmp.path().join("outside/secret.txt"), "secret").unwrap();
+        (tmp, root)
+    }
+
+    #[test]
+    fn resolves_plain_and_encoded_paths() {
+        let (_tmp, root) = fixture();
+        let safe = SafeRoot::new(&root, SymlinkPolicy::WithinRoot).unwrap();
+        let canonical = root.canonicalize().unwrap();
+
+        assert_eq!(safe.resolve("/").unwrap(), canonical);
+        assert_eq!(safe.resolve("/a.txt").unwrap(), canonical.join("a.txt"));
+        assert_eq!(safe.resolve("/sub/./b.txt").unwrap(), canonical.join("sub/b.txt"));
+        assert_eq!(safe.resolve("/%73ub/b%2Etxt").unwrap(), canonical.join("sub/b.txt"));
+        assert_eq!(safe.resolve("/missing"), Err(PathError::NotFound));
+    }
+
+    #[test]
+    fn rejects_traversal_attempts() {
+        let (_tmp, root) = fixture();
+        let safe = SafeRoot::new(&root, SymlinkPolicy::FollowAll).unwrap();
+
+        for attempt in [
+            "/../outside/secret.txt",
+            "/sub/../../outside/secret.txt",
+            "/%2e%2e/outside/secret.txt",
+            "/%2E%2E%2Foutside%2Fsecret.txt",
+            "/sub%2F..%2F..%2Foutside/secret.txt",
+            "/a.txt%00.png",
+        ] {
+            assert_eq!(safe.resolve(attempt), Err(PathError::Forbidden), "{attempt}");
+        }
+
+        // Backslashes only separate on Windows; elsewhere these name files that don't exist
+        let expected = if cfg!(windows) { PathError::Forbidden } else { PathError::NotFound };
+        for attempt in ["/..\\outside\\secret.txt", "/sub\\..\\..\\outside/secret.txt", "/%2e%2e%5coutside%5csecret.txt"] {
+            assert_eq!(safe.resolve(attempt).err().as_ref(), Some(&expected), "{attempt}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backslashes_are_part_of_unix_names() {
+        let (_tmp, root) = fixture();
+        fs::write(root.join("sub/back\\slash.txt"), "x").unwrap();
+        let safe = SafeRoot::new(&root, SymlinkPolicy::WithinRoot).unwrap();
+        let canonical = root.canonicalize().unwrap();
+
+        let href = format!("/sub/{}", encode_segment("back\\slash.txt"));
+        assert_eq!(safe.resolve(&href).unwrap(), canonical.join("sub/back\\slash.txt"));
+        assert_eq!(safe.resolve("/sub/back\\slash.txt").unwrap(), canonical.join("sub/back\\slash.txt"));
+        assert_eq!(safe.resolve_entry("/sub/new%5Cfile.txt").unwrap(), canonical.join("sub/new\\file.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn applies_symlink_policy() {
+        use std::os::unix::fs::symlink;
+
+        let (tmp, root) = fixture();
+        symlink(tmp.path().join("outside"), root.join("escape")).unwrap();
+        symlink(root.join("sub"), root.join("inside")).unwrap();
+
+        let deny = SafeRoot::new(&root, SymlinkPolicy::Deny).unwrap();
+        assert_eq!(deny.resolve("/inside/b.txt"), Err(PathError::Forbidden));
+        assert_eq!(deny.resolve("/escape/secret.txt"), Err(PathError::Forbidden));
+        assert!(!deny.permits(&root.join("inside")));
+
+        let within = SafeRoot::new(&root, SymlinkPolicy::WithinRoot).unwrap();
+        assert!(within.resolve("/inside/b.txt").is_ok());
+        assert_eq!(within.resolve("/escape/secret.txt"), Err(PathError::Forbidden));
+        assert!(within.permits(&root.join("inside")));
+        assert!(!within.permits(&root.join("escape")));
+
+        let follow = SafeRoot::new(&root, SymlinkPolicy::FollowAll).unwrap();
+        assert_eq!(
+            follow.resolve("/escape/secret.txt").unwrap(),
+            tmp.path().join("outside/secret.txt").canonicalize().unwrap()
+        );
+        assert_eq!(follow.resolve("/escape/../a.txt"), Err(PathError::Forbidden));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_entries_without_following_them() {
+        use std::os::unix::fs::symlink;
+
+        let (tmp, root) = fixture();
+        symlink(tmp.path().join("outside"), root.join("escape")).unwrap();
+        let safe = SafeRoot::new(&root, SymlinkPolicy::WithinRoot).unwrap();
+        let canonical = root.canonicalize().unwrap();
+
+        assert_eq!(safe.resolve_entry("/sub/new.txt").unwrap(), canonical.join("sub/new.txt"));
+        assert_eq!(safe.resolve_entry("/sub/").unwrap(), canonical.join("sub"));
+        assert_eq!(safe.resolve_entry("/missing/new.txt"), Err(PathError::NotFound));
+        for attempt in ["/", "/..", "/sub/%2e%2e", "/sub%2F..%2Fx", "/escape", "/escape/x", "/a%00"] {
+            assert_eq!(safe.resolve_entry(attempt), Err(PathError::Forbidden), "{attempt}");
+        }
+    }
+}
@@ -0,0 +1,273 @@
+// 🔀 Reverse-proxy mode: forward every request to a local HTTP server
+use std::{fmt, net::SocketAddr, sync::Arc};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode, Uri},
+    response::Response,
+    Router,
+};
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::{TokioExecutor, TokioIo},
+};
+
+/// Headers that describe a single hop and must not be forwarded
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// The local server being exposed (`:3000`, `http://localhost:8888/lab`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    /// `host:port`
+    authority: String,
+    /// Path prefix prepended to every request, without a trailing slash
+    base: String,
+}
+
+impl Target {
+    /// `Ok(None)` when `spec` doesn't look like a proxy target (it's a path)
+    pub fn parse(spec: &str) -> anyhow::Result<Option<Self>> {
+        if let Some(port) = spec.strip_prefix(':') {
+            let port = parse_port(port)?;
+            return Ok(Some(Self {
+                authority: format!("localhost:{}", port),
+                base: String::new(),
+            }));
+        }
+        if spec.starts_with("https://") {
+            anyhow::bail!("HTTPS upstreams aren't supported; point yeet at the server's plain http:// port");
+        }
+        let Some(rest) = spec.strip_prefix("http://") else {
+            return Ok(None);
+        };
+
+        let (authority, base) = rest.find('/').map_or((rest, ""), |i| rest.split_at(i));
+        if authority.is_empty() || authority.contains('@') {
+            anyhow::bail!("Invalid upstream URL: {}", spec);
+        }
+        // Bracketed IPv6 hosts contain colons of their own
+        let authority = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => format!("{}:{}", host, parse_port(port)?),
+            _ => format!("{}:80", authority),
+        };
+        if base.contains(['?', '#']) {
+            anyhow::bail!("Upstream URL can't carry a query or fragment: {}", spec);
+        }
+        Ok(Some(Self {
+            authority,
+            base: base.trim_end_matches('/').to_string(),
+        }))
+    }
+
+    /// Upstream URI for an incoming request path (and query)
+    fn uri_for(&self, incoming: &Uri) -> Result<Uri, axum::http::Error> {
+        let path_and_query = incoming.path_and_query().map_or("/", |pq| pq.as_str());
+        Uri::builder()
+            .scheme("http")
+            .authority(self.authority.as_str())
+            .path_and_query(format!("{}{}", self.base, path_and_query))
+            .build()
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "http://{}{}", self.authority, self.base)
+    }
+}
+
+fn parse_port(port: &str) -> anyhow::Result<u16> {
+    match port.parse::<u16>() {
+        Ok(port) if port != 0 => Ok(port),
+        _ => anyhow::bail!("Invalid upstream port: {}", port),
+    }
+}
+
+struct Proxy {
+    target: Target,
+    client: Client<HttpConnector, Body>,
+    /// The tunnel's header carrying the real client address, if it has one
+    client_ip_header: Option<&'static str>,
+}
+
+/// Catch-all router forwarding to `target`; merge it after any yeet routes
+pub fn router<S: Clone + Send + Sync + 'static>(target: Target, client_ip_header: Option<&'static str>) -> Router<S> {
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    Router::new().fallback(forward).with_state(Arc::new(Proxy {
+        target,
+        client,
+        client_ip_header,
+    }))
+}
+
+async fn forward(
+    State(proxy): State<Arc<Proxy>>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    mut req: Request,
+) -> Response {
+    let upgrade = upgrade_protocol(req.headers());
+    // Grab the client side of a WebSocket (or other) upgrade before the request is consumed
+    let client_upgrade = upgrade.is_some().then(|| hyper::upgrade::on(&mut req));
+
+    let (mut parts, body) = req.into_parts();
+    parts.uri = match proxy.target.uri_for(&parts.uri) {
+        Ok(uri) => uri,
+        Err(_) => return error(StatusCode::BAD_REQUEST, "Bad request path".to_string()),
+    };
+    strip_hop_by_hop(&mut parts.headers);
+    if let Some(protocol) = upgrade {
+        parts.headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        parts.headers.insert(header::UPGRADE, protocol);
+    }
+    super::auth::strip_own_credentials(&mut parts);
+    let peer = peer.map(|ConnectInfo(addr)| addr.ip());
+    let client = super::stats::client_addr(&parts.headers, peer, proxy.client_ip_header);
+    add_forwarded_headers(&mut parts.headers, client);
+
+    let mut response = match proxy.client.request(Request::from_parts(parts, body)).await {
+        Ok(response) => response,
+        Err(e) => {
+            return error(
+                StatusCode::BAD_GATEWAY,
+                format!("Upstream {} is not responding: {}", proxy.target, e),
+            )
+        }
+    };
+
+    if response.status() == StatusCode::SWITCHING_PROTOCOLS {
+        if let Some(client_upgrade) = client_upgrade {
+            let upstream_upgrade = hyper::upgrade::on(&mut response);
+            tokio::spawn(async move {
+                if let Ok((client, upstream)) = tokio::try_join!(client_upgrade, upstream_upgrade) {
+                    let _ = tokio::io::copy_bidirectional(&mut TokioIo::new(client), &mut TokioIo::new(upstream)).await;
+                }
+            });
+        }
+    } else {
+        strip_hop_by_hop(response.headers_mut());
+    }
+    // Streamed straight through; chunked and long-lived responses aren't buffered
+    response.map(Body::new)
+}
+
+/// The requested protocol when this is an upgrade (`Connection: upgrade`)
+fn upgrade_protocol(headers: &HeaderMap) -> Option<HeaderValue> {
+    let wants_upgrade = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+    wants_upgrade.then(|| headers.get(header::UPGRADE).cloned()).flatten()
+}
+
+/// Remove hop-by-hop headers, including any the Connection header names
+fn strip_hop_by_hop(headers: &mut HeaderMap) {
+    let named: Vec<HeaderName> = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect();
+    for name in named {
+        headers.remove(name);
+    }
+    for name in HOP_BY_HOP {
+        headers.remove(*name);
+    }
+}
+
+/// X-Forwarded-For/-Host, with Host rewritten to the upstream's own (any
+/// X-Forwarded-Proto from the tunnel is passed along untouched). `client` is
+/// the real client as far as yeet can tell, not the tunnel's loopback hop.
+fn add_forwarded_headers(headers: &mut HeaderMap, client: Option<String>) {
+    if let Some(client) = client {
+        let chain = match headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
+            // Tunnels that already list the client there don't get it twice
+            Some(existing) if existing.rsplit(',').next().map(str::trim) == Some(client.as_str()) => existing.to_string(),
+            Some(existing) => format!("{}, {}", existing, client),
+            None => client,
+        };
+        if let Ok(value) = HeaderValue::from_str(&chain) {
+            headers.insert("x-forwarded-for", value);
+        }
+    }
+    if let Some(host) = headers.remove(header::HOST) {
+        headers.insert("x-forwarded-host", host);
+    }
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(message))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_targets() {
+        let target = |spec| Target::parse(spec).unwrap().unwrap();
+
+        assert_eq!(target(":3000").to_string(), "http://localhost:3000");
+        assert_eq!(target("http://localhost:3000").to_string(), "http://localhost:3000");
+        assert_eq!(target("http://127.0.0.1:8888/lab/").to_string(), "http://127.0.0.1:8888/lab");
+        assert_eq!(target("http://devbox").to_string(), "http://devbox:80");
+        assert_eq!(target("http://[::1]:5173").to_string(), "http://[::1]:5173");
+        assert_eq!(target("http://[::1]").to_string(), "http://[::1]:80");
+
+        assert_eq!(Target::parse("./site").unwrap(), None);
+        assert_eq!(Target::parse("notes:3000.txt").unwrap(), None);
+        for bad in [":0", ":http", "https://localhost:3000", "http://", "http://user@host:1", "http://h:1/?q"] {
+            assert!(Target::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn rewrites_request_uris() {
+        let target = Target::parse("http://localhost:8888/lab").unwrap().unwrap();
+        let uri: Uri = "/tree/notebook.ipynb?kernel=1".parse().unwrap();
+        assert_eq!(
+            target.uri_for(&uri).unwrap().to_string(),
+            "http://localhost:8888/lab/tree/notebook.ipynb?kernel=1"
+        );
+    }
+
+    #[test]
+    fn forwards_the_real_client_address() {
+        let forwarded_for = |xff: Option<&str>, tunnel_header: Option<&str>, peer: &str| {
+            let mut headers = HeaderMap::new();
+            if let Some(xff) = xff {
+                headers.insert("x-forwarded-for", HeaderValue::from_str(xff).unwrap());
+            }
+            headers.insert("cf-connecting-ip", HeaderValue::from_static("203.0.113.9"));
+            let client = super::super::stats::client_addr(&headers, Some(peer.parse().unwrap()), tunnel_header);
+            add_forwarded_headers(&mut headers, client);
+            headers["x-forwarded-for"].to_str().unwrap().to_string()
+        };
+
+        // Behind cloudflared the peer is always loopback
+        assert_eq!(forwarded_for(None, Some("cf-connecting-ip"), "127.0.0.1"), "203.0.113.9");
+        assert_eq!(forwarded_for(Some("203.0.113.9"), Some("cf-connecting-ip"), "127.0.0.1"), "203.0.113.9");
+        assert_eq!(forwarded_for(Some("10.0.0.1"), Some("cf-connecting-ip"), "127.0.0.1"), "10.0.0.1, 203.0.113.9");
+        // Without such a tunnel (or from a LAN peer) the header is just text
+        assert_eq!(forwarded_for(None, None, "127.0.0.1"), "127.0.0.1");
+        assert_eq!(forwarded_for(None, Some("cf-connecting-ip"), "192.168.1.5"), "192.168.1.5");
+    }
+}
@@ -0,0 +1,178 @@
+// 📦 HTTP Range header parsing (RFC 9110 byte ranges)
+
+/// Upper bound on ranges honoured in one request; anything above is
+/// treated as abusive and the header is ignored (full 200 response)
+const MAX_RANGES: usize = 32;
+
+/// An inclusive byte range within a representation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Value for the Content-Range header
+    pub fn content_range(&self, total: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, total)
+    }
+}
+
+/// Outcome of evaluating a Range header against a representation
+#[derive(Debug, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable Range header - send the whole representation
+    Full,
+    /// One or more satisfiable ranges, sorted and coalesced
+    Partial(Vec<ByteRange>),
+    /// Syntactically valid but none of the ranges overlap the content (416)
+    Unsatisfiable,
+}
+
+/// Parse a `Range` header value for a representation of `len` bytes.
+///
+/// Malformed headers and non-byte units are ignored as the RFC requires,
+/// which means the caller falls back to a normal 200 response.
+pub fn parse(header: &str, len: u64) -> RangeRequest {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {
+        return RangeRequest::Full;
+    };
+
+    let mut ranges = Vec::new();
+    let mut count = 0;
+    for part in spec.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        count += 1;
+        if count > MAX_RANGES {
+            return RangeRequest::Full;
+        }
+
+        let Some((first, last)) = part.split_once('-') else {
+            return RangeRequest::Full;
+        };
+        let (first, last) = (first.trim(), last.trim());
+
+        let range = if first.is_empty() {
+            // Suffix range: the last N bytes
+            let Ok(suffix) = last.parse::<u64>() else {
+                return RangeRequest::Full;
+            };
+            if suffix == 0 || len == 0 {
+                None
+            } else {
+                Some(ByteRange {
+                    start: len.saturating_sub(suffix),
+                    end: len - 1,
+                })
+            }
+        } else {
+            let Ok(start) = first.parse::<u64>() else {
+                return RangeRequest::Full;
+            };
+            let end = if last.is_empty() {
+                u64::MAX
+            } else {
+                match last.parse::<u64>() {
+                    Ok(end) if end >= start => end,
+                    _ => return RangeRequest::Full,
+                }
+            };
+            if start >= len {
+                None
+            } else {
+                Some(ByteRange {
+                    start,
+                    end: end.min(len - 1),
+                })
+            }
+        };
+
+        ranges.extend(range);
+    }
+
+    if count == 0 {
+        return RangeRequest::Full;
+    }
+    if ranges.is_empty() {
+        return RangeRequest::Unsatisfiable;
+    }
+
+    RangeRequest::Partial(coalesce(ranges))
+}
+
+/// Sort ranges and merge the ones that overlap or touch, so a client
+/// can't make us send the same bytes many times over
+fn coalesce(mut ranges: Vec<ByteRange>) -> Vec<ByteRange> {
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<ByteRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(prev) if range.start <= prev.end.saturating_add(1) => {
+                prev.end = prev.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partial(ranges: &[(u64, u64)]) -> RangeRequest {
+        RangeRequest::Partial(ranges.iter().map(|&(start, end)| ByteRange { start, end }).collect())
+    }
+
+    #[test]
+    fn parses_single_suffix_and_open_ranges() {
+        assert_eq!(parse("bytes=0-99", 1000), partial(&[(0, 99)]));
+        assert_eq!(parse("bytes=-500", 1000), partial(&[(500, 999)]));
+        assert_eq!(parse("bytes=500-", 1000), partial(&[(500, 999)]));
+        // Ends past the file and suffixes longer than it are clamped
+        assert_eq!(parse("bytes=900-5000", 1000), partial(&[(900, 999)]));
+        assert_eq!(parse("bytes=-5000", 1000), partial(&[(0, 999)]));
+        assert_eq!(parse(" bytes=500-999 ", 1000), partial(&[(500, 999)]));
+    }
+
+    #[test]
+    fn coalesces_overlapping_and_adjacent_ranges() {
+        assert_eq!(parse("bytes=50-99,0-49,200-299", 1000), partial(&[(0, 99), (200, 299)]));
+        assert_eq!(parse("bytes=0-10,5-20,-990", 1000), partial(&[(0, 999)]));
+        assert_eq!(parse("bytes=0-0,0-0,0-0", 1000), partial(&[(0, 0)]));
+    }
+
+    #[test]
+    fn caps_the_number_of_ranges() {
+        let many = |n: usize| {
+            let parts: Vec<String> = (0..n).map(|i| format!("{}-{}", i * 10, i * 10)).collect();
+            format!("bytes={}", parts.join(","))
+        };
+        assert!(matches!(parse(&many(MAX_RANGES), 1000), RangeRequest::Partial(r) if r.len() == MAX_RANGES));
+        assert_eq!(parse(&many(MAX_RANGES + 1), 1000), RangeRequest::Full);
+    }
+
+    #[test]
+    fn reports_unsatisfiable_ranges() {
+        assert_eq!(parse("bytes=1000-", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(parse("bytes=2000-3000,1000-1001", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(parse("bytes=-0", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(parse("bytes=0-", 0), RangeRequest::Unsatisfiable);
+        // One satisfiable range is enough
+        assert_eq!(parse("bytes=2000-,10-19", 1000), partial(&[(10, 19)]));
+    }
+
+    #[test]
+    fn ignores_malformed_headers() {
+        for header in ["items=0-9", "bytes", "bytes=", "bytes=abc", "bytes=9-0", "bytes=0-9,x-y", "bytes=--5", "bytes=1-2-3"] {
+            assert_eq!(parse(header, 1000), RangeRequest::Full, "{}", header);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+// 🔍 Search the whole shared tree by name, extension, size, age and (for text) contents
+use std::{
+    fs,
+    io::Read,
+    path::Path,
+    sync::Arc,
+    time::SystemTime,
+};
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+
+use super::{
+    expiry::parse_duration,
+    listing::{self, Base, Found},
+    paths::{self, PathError, SafeRoot},
+    upload::parse_size,
+};
+use crate::shared::{SearchHit, SearchResults};
+
+const DEFAULT_LIMIT: usize = 200;
+const MAX_LIMIT: usize = 1000;
+/// Larger files are skipped by content search
+const GREP_MAX_FILE: u64 = 4 * 1024 * 1024;
+/// Total bytes read by one content search before it gives up
+const GREP_BUDGET: u64 = 256 * 1024 * 1024;
+/// Files with a NUL byte this early on are treated as binary
+const BINARY_SNIFF: usize = 8192;
+const SNIPPET_CHARS: usize = 160;
+
+/// `/api/search?q=*.csv&ext=csv,tsv&min_size=1M&max_size=1G&newer=7d&older=1h&grep=loss&path=runs`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    /// Name glob (`*`, `?`) or, without wildcards, a substring; case-insensitive
+    q: String,
+    /// Only search below this directory (from the share root)
+    path: String,
+    /// Comma-separated extensions
+    ext: String,
+    min_size: Option<String>,
+    max_size: Option<String>,
+    /// Modified within this long (`7d`, `12h`)
+    newer: Option<String>,
+    /// Modified longer ago than this
+    older: Option<String>,
+    /// Text that files must contain (case-insensitive, text files only)
+    grep: String,
+    limit: Option<usize>,
+}
+
+/// Parsed and validated search criteria
+struct Criteria {
+    name: Option<Regex>,
+    extensions: Vec<String>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    newer_than: Option<SystemTime>,
+    older_than: Option<SystemTime>,
+    grep: Option<String>,
+    limit: usize,
+}
+
+impl Criteria {
+    fn parse(query: &SearchQuery) -> Result<Self, String> {
+        let size = |value: &Option<String>| value.as_deref().map(parse_size).transpose();
+        let ago = |value: &Option<String>| -> Result<Option<SystemTime>, String> {
+            value
+                .as_deref()
+                .map(|v| parse_duration(v).map(|d| SystemTime::now() - d))
+                .transpose()
+        };
+        Ok(Self {
+            name: name_pattern(query.q.trim())?,
+            extensions: query
+                .ext
+                .split(',')
+                .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            min_size: size(&query.min_size)?,
+            max_size: size(&query.max_size)?,
+            newer_than: ago(&query.newer)?,
+            older_than: ago(&query.older)?,
+            grep: Some(query.grep.to_lowercase()).filter(|grep| !grep.is_empty()),
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        })
+    }
+
+    /// Size, extension and content filters only make sense for files
+    fn files_only(&self) -> bool {
+        !self.extensions.is_empty() || self.min_size.is_some() || self.max_size.is_some() || self.grep.is_some()
+    }
+
+    /// Everything except the content search
+    fn matches(&self, found: &Found) -> bool {
+        let metadata = &found.metadata;
+        if self.files_only() && !metadata.is_file() {
+            return false;
+        }
+        let name = found.path.file_name().unwrap_or_default().to_string_lossy();
+        if self.name.as_ref().is_some_and(|pattern| !pattern.is_match(&name)) {
+            return false;
+        }
+        if !self.extensions.is_empty() {
+            let ext = Path::new(&*name)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if !self.extensions.contains(&ext) {
+                return false;
+            }
+        }
+        let len = metadata.len();
+        if self.min_size.is_some_and(|min| len < min) || self.max_size.is_some_and(|max| len > max) {
+            return false;
+        }
+        if self.newer_than.is_some() || self.older_than.is_some() {
+            let Ok(modified) = metadata.modified() else {
+                return false;
+            };
+            if self.newer_than.is_some_and(|t| modified < t) || self.older_than.is_some_and(|t| modified > t) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// `*.csv` -> anchored glob, `report` -> substring, empty -> anything
+fn name_pattern(q: &str) -> Result<Option<Regex>, String> {
+    if q.is_empty() {
+        return Ok(None);
+    }
+    let pattern = if q.contains(['*', '?']) {
+        let mut pattern = String::from("^");
+        for c in q.chars() {
+            match c {
+                '*' => pattern.push_str(".*"),
+                '?' => pattern.push('.'),
+                c => pattern.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        pattern.push('$');
+        pattern
+    } else {
+        regex::escape(q)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+/// `/api/search` for directory shares
+pub fn router<S: Clone + Send + Sync + 'static>(root: SafeRoot) -> Router<S> {
+    Router::new()
+        .route("/api/search", get(search))
+        .with_state(Arc::new(root))
+}
+
+async fn search(State(root): State<Arc<SafeRoot>>, Query(query): Query<SearchQuery>) -> Response {
+    let criteria = match Criteria::parse(&query) {
+        Ok(criteria) => criteria,
+        Err(e) => return plain(StatusCode::BAD_REQUEST, format!("Invalid search: {}", e)),
+    };
+    let uri_path: String = query
+        .path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", paths::encode_segment(segment)))
+        .collect();
+    let dir = match root.resolve(&uri_path) {
+        Ok(dir) if dir.is_dir() => dir,
+        Ok(_) => return plain(StatusCode::BAD_REQUEST, "Not a directory".to_string()),
+        Err(PathError::Forbidden) => return plain(StatusCode::FORBIDDEN, "Forbidden".to_string()),
+        Err(PathError::NotFound) => return plain(StatusCode::NOT_FOUND, "Not found".to_string()),
+    };
+
+    let results = tokio::task::spawn_blocking(move || run(&dir, &uri_path, &root, &criteria)).await;
+    match results {
+        Ok(Ok(results)) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(Body::from(serde_json::to_string(&results).unwrap_or_default()))
+            .unwrap(),
+        _ => plain(StatusCode::INTERNAL_SERVER_ERROR, "Search failed".to_string()),
+    }
+}
+
+fn run(dir: &Path, uri_path: &str, root: &SafeRoot, criteria: &Criteria) -> std::io::Result<SearchResults> {
+    let found = listing::walk_tree(dir, root)?;
+    let base = Base::new(uri_path);
+    let mut results = SearchResults {
+        scanned: 0,
+        truncated: false,
+        hits: Vec::new(),
+    };
+    let mut budget = GREP_BUDGET;
+
+    for found in &found {
+        results.scanned += 1;
+        if !criteria.matches(found) {
+            continue;
+        }
+        let (line, snippet) = match &criteria.grep {
+            Some(needle) => {
+                let len = found.metadata.len();
+                if len > GREP_MAX_FILE {
+                    continue;
+                }
+                if len > budget {
+                    results.truncated = true;
+                    break;
+                }
+                budget -= len;
+                match grep_file(&found.path, needle) {
+                    Some((line, snippet)) => (Some(line), Some(snippet)),
+                    None => continue,
+                }
+            }
+            None => (None, None),
+        };
+        if results.hits.len() == criteria.limit {
+            results.truncated = true;
+            break;
+        }
+        results.hits.push(SearchHit {
+            entry: base.entry(found),
+            line,
+            snippet,
+        });
+    }
+    Ok(results)
+}
+
+/// First line containing `needle` (already lowercased) in a text file
+fn grep_file(path: &Path, needle: &str) -> Option<(usize, String)> {
+    let mut bytes = Vec::new();
+    fs::File::open(path).ok()?.take(GREP_MAX_FILE).read_to_end(&mut bytes).ok()?;
+    if bytes[..bytes.len().min(BINARY_SNIFF)].contains(&0) {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    text.lines().enumerate().find_map(|(i, line)| {
+        line.to_lowercase().contains(needle).then(|| {
+            let snippet: String = line.trim().chars().take(SNIPPET_CHARS).collect();
+            (i + 1, snippet)
+        })
+    })
+}
+
+fn plain(status: StatusCode, message: String) -> Response {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(message))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::paths::SymlinkPolicy;
+
+    fn search(root: &Path, query: SearchQuery) -> Vec<(String, Option<usize>)> {
+        let safe_root = SafeRoot::new(root, SymlinkPolicy::Deny).unwrap();
+        let criteria = Criteria::parse(&query).unwrap();
+        run(root, "", &safe_root, &criteria)
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| (hit.entry.path, hit.line))
+            .collect()
+    }
+
+    #[test]
+    fn filters_by_name_extension_size_and_content() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("runs/Jan")).unwrap();
+        fs::write(tmp.path().join("runs/Jan/metrics.csv"), "step,loss\n1,0.9\n2,NaN\n").unwrap();
+        fs::write(tmp.path().join("runs/notes.md"), "loss looked fine").unwrap();
+        fs::write(tmp.path().join("weights.bin"), [0u8; 2048]).unwrap();
+
+        let query = |f: fn(&mut SearchQuery)| {
+            let mut query = SearchQuery::default();
+            f(&mut query);
+            query
+        };
+        let hits = |q: SearchQuery| search(tmp.path(), q);
+
+        assert_eq!(hits(query(|q| q.q = "*.CSV".into())), [("runs/Jan/metrics.csv".into(), None)]);
+        assert_eq!(hits(query(|q| q.q = "jan".into())), [("runs/Jan".into(), None)]);
+        assert_eq!(hits(query(|q| q.ext = "md, .bin".into())).len(), 2);
+        assert_eq!(hits(query(|q| q.min_size = Some("1K".into()))), [("weights.bin".into(), None)]);
+        assert_eq!(hits(query(|q| q.grep = "nan".into())), [("runs/Jan/metrics.csv".into(), Some(3))]);
+        // Binary files are never content-matched
+        assert!(hits(query(|q| q.grep = "\0".into())).is_empty());
+        assert!(Criteria::parse(&query(|q| q.newer = Some("soon".into()))).is_err());
+    }
+}
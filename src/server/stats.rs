@@ -0,0 +1,324 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use http_body::{Frame, SizeHint};
+use pin_project_lite::pin_project;
+
+use crate::shared::{RequestLog, ServerStats};
+
+/// Number of request log entries kept for /api/logs
+const LOG_CAPACITY: usize = 500;
+/// Sliding window used for requests_per_minute
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+/// Sliding window (in seconds) used for current_speed_bps
+const SPEED_WINDOW_SECS: u64 = 5;
+
+/// Shared request accounting for the daemon server
+pub struct StatsTracker {
+    started: Instant,
+    /// Header the active tunnel sets to the real client address, if any
+    client_ip_header: Option<&'static str>,
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    /// Last time a request started, finished or sent bytes
+    last_activity: Instant,
+    total_requests: u64,
+    total_bytes_sent: u64,
+    active_connections: u32,
+    unique_ips: HashSet<String>,
+    /// Start times of requests seen within RATE_WINDOW
+    recent_requests: VecDeque<Instant>,
+    /// Bytes written per second (seconds since start), newest at the back
+    recent_bytes: VecDeque<(u64, u64)>,
+    /// Ring buffer of finished requests, newest at the back
+    logs: VecDeque<RequestLog>,
+}
+
+impl StatsTracker {
+    pub fn new(client_ip_header: Option<&'static str>) -> Self {
+        Self {
+            started: Instant::now(),
+            client_ip_header,
+            inner: Mutex::new(Inner {
+                last_activity: Instant::now(),
+                total_requests: 0,
+                total_bytes_sent: 0,
+                active_connections: 0,
+                unique_ips: HashSet::new(),
+                recent_requests: VecDeque::new(),
+                recent_bytes: VecDeque::new(),
+                logs: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Snapshot of the current counters for /api/stats
+    pub fn snapshot(&self) -> ServerStats {
+        let now = Instant::now();
+        let now_sec = self.second(now);
+        let mut inner = self.inner.lock().unwrap();
+        inner.prune(now, now_sec);
+
+        // Average over the full window, excluding the second still in progress
+        let window_bytes: u64 = inner
+            .recent_bytes
+            .iter()
+            .filter(|(sec, _)| *sec < now_sec)
+            .map(|(_, bytes)| bytes)
+            .sum();
+        let window_secs = SPEED_WINDOW_SECS.min(now_sec).max(1);
+
+        ServerStats {
+            uptime_secs: now.duration_since(self.started).as_secs(),
+            total_requests: inner.total_requests,
+            total_bytes_sent: inner.total_bytes_sent,
+            current_speed_bps: window_bytes / window_secs,
+            active_connections: inner.active_connections,
+            unique_ips: inner.unique_ips.len() as u32,
+            requests_per_minute: inner.recent_requests.len() as u32,
+        }
+    }
+
+    /// Most recent request logs, newest first
+    pub fn logs(&self) -> Vec<RequestLog> {
+        let inner = self.inner.lock().unwrap();
+        inner.logs.iter().rev().cloned().collect()
+    }
+
+    /// How long the server has been completely idle, or `None` while a
+    /// request is still in flight
+    pub fn idle_for(&self) -> Option<Duration> {
+        let inner = self.inner.lock().unwrap();
+        (inner.active_connections == 0).then(|| inner.last_activity.elapsed())
+    }
+
+    fn begin(&self, ip: &str) {
+        let now = Instant::now();
+        let now_sec = self.second(now);
+        let mut inner = self.inner.lock().unwrap();
+        inner.last_activity = now;
+        inner.total_requests += 1;
+        inner.active_connections += 1;
+        if !inner.unique_ips.contains(ip) {
+            inner.unique_ips.insert(ip.to_string());
+        }
+        inner.recent_requests.push_back(now);
+        inner.prune(now, now_sec);
+    }
+
+    fn record_bytes(&self, bytes: u64) {
+        let now = Instant::now();
+        let now_sec = self.second(now);
+        let mut inner = self.inner.lock().unwrap();
+        inner.last_activity = now;
+        inner.total_bytes_sent += bytes;
+        match inner.recent_bytes.back_mut() {
+            Some((sec, total)) if *sec == now_sec => *total += bytes,
+            _ => inner.recent_bytes.push_back((now_sec, bytes)),
+        }
+        inner.prune(now, now_sec);
+    }
+
+    fn finish(&self, log: RequestLog) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.last_activity = Instant::now();
+        inner.active_connections = inner.active_connections.saturating_sub(1);
+        if inner.logs.len() == LOG_CAPACITY {
+            inner.logs.pop_front();
+        }
+        inner.logs.push_back(log);
+    }
+
+    fn second(&self, at: Instant) -> u64 {
+        at.duration_since(self.started).as_secs()
+    }
+}
+
+impl Default for StatsTracker {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Inner {
+    fn prune(&mut self, now: Instant, now_sec: u64) {
+        while let Some(&at) = self.recent_requests.front() {
+            if now.duration_since(at) < RATE_WINDOW {
+                break;
+            }
+            self.recent_requests.pop_front();
+        }
+        while let Some(&(sec, _)) = self.recent_bytes.front() {
+            if sec + SPEED_WINDOW_SECS >= now_sec {
+                break;
+            }
+            self.recent_bytes.pop_front();
+        }
+    }
+}
+
+/// Middleware that records every request passing through the router.
+/// The log entry is written once the response body has been fully sent
+/// (or dropped), so `size_bytes` reflects what actually went over the wire.
+pub async fn track(State(stats): State<Arc<StatsTracker>>, req: Request, next: Next) -> Response {
+    let ip = client_ip(&req, stats.client_ip_header);
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-")
+        .to_string();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    stats.begin(&ip);
+    let response = next.run(req).await;
+
+    let guard = RequestGuard {
+        stats,
+        log: RequestLog {
+            timestamp,
+            method,
+            path,
+            status: response.status().as_u16(),
+            size_bytes: 0,
+            user_agent,
+            ip,
+        },
+    };
+
+    let (parts, body) = response.into_parts();
+    Response::from_parts(parts, Body::new(CountingBody { inner: body, guard }))
+}
+
+/// Best-effort client address. Behind a tunnel every socket peer is
+/// localhost, so the tunnel's own client header is used; it's only trusted
+/// when that tunnel is in use and the request came through it.
+fn client_ip(req: &Request, client_ip_header: Option<&str>) -> String {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    client_addr(req.headers(), peer, client_ip_header).unwrap_or_else(|| "unknown".to_string())
+}
+
+/// `client_ip` for code that has already taken the request apart
+pub(super) fn client_addr(headers: &HeaderMap, peer: Option<IpAddr>, client_ip_header: Option<&str>) -> Option<String> {
+    let forwarded = client_ip_header
+        .filter(|_| peer.is_none_or(|ip| ip.is_loopback()))
+        .and_then(|name| headers.get(name))
+        .and_then(|v| v.to_str().ok())
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty());
+    forwarded.or_else(|| peer.map(|ip| ip.to_string()))
+}
+
+/// Tracks one in-flight request; finalizes the log entry when dropped
+struct RequestGuard {
+    stats: Arc<StatsTracker>,
+    log: RequestLog,
+}
+
+impl RequestGuard {
+    fn record(&mut self, bytes: u64) {
+        self.log.size_bytes += bytes;
+        self.stats.record_bytes(bytes);
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        let log = std::mem::take(&mut self.log);
+        self.stats.finish(log);
+    }
+}
+
+pin_project! {
+    /// Response body wrapper that counts data frames as they are polled
+    struct CountingBody {
+        #[pin]
+        inner: Body,
+        guard: RequestGuard,
+    }
+}
+
+impl http_body::Body for CountingBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let poll = this.inner.poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                this.guard.record(data.len() as u64);
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn request(peer: [u8; 4], headers: &[(&str, &str)]) -> Request {
+        let mut builder = Request::builder().uri("/");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let mut req = builder.body(Body::empty()).unwrap();
+        req.extensions_mut().insert(ConnectInfo(SocketAddr::from((Ipv4Addr::from(peer), 4000))));
+        req
+    }
+
+    #[test]
+    fn only_trusts_the_active_tunnels_client_header() {
+        let spoofed = [("cf-connecting-ip", "203.0.113.9"), ("x-forwarded-for", "198.51.100.7")];
+
+        // Cloudflare tunnel: cloudflared connects from localhost and sets cf-connecting-ip
+        let req = request([127, 0, 0, 1], &spoofed);
+        assert_eq!(client_ip(&req, Some("cf-connecting-ip")), "203.0.113.9");
+
+        // LAN / SSH: no header is trusted, the peer is the client
+        let req = request([192, 168, 1, 5], &spoofed);
+        assert_eq!(client_ip(&req, None), "192.168.1.5");
+        let req = request([127, 0, 0, 1], &spoofed);
+        assert_eq!(client_ip(&req, None), "127.0.0.1");
+
+        // Cloudflare configured, but someone reached the port directly
+        let req = request([192, 168, 1, 5], &spoofed);
+        assert_eq!(client_ip(&req, Some("cf-connecting-ip")), "192.168.1.5");
+    }
+}
@@ -0,0 +1,498 @@
+// 📥 Share piped input (`pg_dump | yeet -`): streamed once, or spooled to disk
+use std::{
+    io::{self, Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    response::Response,
+    routing::get,
+    Router,
+};
+use futures_util::{stream, Stream, StreamExt};
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt},
+    sync::watch,
+};
+use tokio_util::{io::ReaderStream, sync::CancellationToken};
+
+use super::{content_type, files, paths};
+use crate::state::{self, StateHandle, StdinProgress};
+
+/// Read size for stdin and the spool file
+const CHUNK_SIZE: usize = 256 * 1024;
+/// How often read progress is written to the share state (for the TUI)
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct StdinConfig {
+    /// Download file name (--name)
+    pub name: String,
+    /// Spool to disk, up to this many bytes, so everyone can download (--spool)
+    pub spool_limit: Option<u64>,
+}
+
+/// Where the spooling thread has got to
+#[derive(Debug, Clone)]
+enum Spooled {
+    Reading(u64),
+    Done(u64),
+    Failed(String),
+}
+
+type AsyncInput = Box<dyn AsyncRead + Send + Unpin>;
+
+/// What gets shared: stdin, or a stand-in for it in tests
+enum Input {
+    Stream(AsyncInput),
+    Spool {
+        reader: Box<dyn Read + Send>,
+        path: PathBuf,
+        limit: u64,
+    },
+}
+
+enum Source {
+    /// Handed to the first downloader, then gone
+    Stream(Mutex<Option<AsyncInput>>),
+    /// Growing file on disk, complete once stdin hits EOF
+    Spool {
+        path: PathBuf,
+        progress: watch::Receiver<Spooled>,
+    },
+}
+
+struct StdinShare {
+    name: String,
+    source: Source,
+    state: StateHandle,
+    shutdown: CancellationToken,
+}
+
+/// Routes serving stdin at `/` and `/<name>`. In spool mode this starts
+/// reading stdin right away; otherwise nothing is read until someone downloads.
+pub fn router<S: Clone + Send + Sync + 'static>(
+    config: StdinConfig,
+    share_id: &str,
+    state: StateHandle,
+    shutdown: CancellationToken,
+) -> io::Result<Router<S>> {
+    let input = match config.spool_limit {
+        Some(limit) => Input::Spool {
+            reader: Box::new(io::stdin()),
+            path: state::spool_dir().join(share_id).join(&config.name),
+            limit,
+        },
+        None => Input::Stream(Box::new(tokio::io::stdin())),
+    };
+    share(config.name, input, state, shutdown)
+}
+
+/// `router` for any input, so tests needn't touch the real stdin
+fn share<S: Clone + Send + Sync + 'static>(
+    name: String,
+    input: Input,
+    state: StateHandle,
+    shutdown: CancellationToken,
+) -> io::Result<Router<S>> {
+    let spooled = matches!(input, Input::Spool { .. });
+    let source = match input {
+        Input::Spool { reader, path, limit } => {
+            let dir = path.parent().unwrap().to_path_buf();
+            std::fs::create_dir_all(&dir)?;
+            let (tx, progress) = watch::channel(Spooled::Reading(0));
+            spawn_spooler(reader, path.clone(), limit, tx, state.clone(), shutdown.clone());
+
+            // The spool only lives as long as the share
+            let cleanup = shutdown.clone();
+            tokio::spawn(async move {
+                cleanup.cancelled().await;
+                let _ = tokio::fs::remove_dir_all(dir).await;
+            });
+            Source::Spool { path, progress }
+        }
+        Input::Stream(reader) => Source::Stream(Mutex::new(Some(reader))),
+    };
+    state.update(|s| {
+        s.stdin = Some(StdinProgress {
+            spooled,
+            ..Default::default()
+        })
+    });
+
+    let share = Arc::new(StdinShare {
+        name,
+        source,
+        state,
+        shutdown,
+    });
+    let named = format!("/{}", paths::encode_segment(&share.name));
+    Ok(Router::new()
+        .route("/", get(download))
+        .route(&named, get(download))
+        .with_state(share))
+}
+
+async fn download(State(share): State<Arc<StdinShare>>, method: Method, uri: Uri, headers: HeaderMap) -> Response {
+    let inline = content_type::wants_inline(uri.query());
+    match &share.source {
+        Source::Spool { path, progress } => {
+            let spooled = progress.borrow().clone();
+            match spooled {
+                Spooled::Done(_) => files::serve_file(path, &headers, inline).await,
+                Spooled::Failed(reason) => plain(StatusCode::GONE, reason),
+                Spooled::Reading(_) => {
+                    let builder = streaming_headers(&share.name, inline);
+                    if method == Method::HEAD {
+                        return builder.body(Body::empty()).unwrap();
+                    }
+                    builder
+                        .body(Body::from_stream(follow_spool(path.clone(), progress.clone())))
+                        .unwrap()
+                }
+            }
+        }
+        Source::Stream(slot) => {
+            let builder = streaming_headers(&share.name, inline);
+            // HEAD must not use up the one and only read of stdin
+            if method == Method::HEAD {
+                return builder.body(Body::empty()).unwrap();
+            }
+            let Some(stdin) = slot.lock().unwrap().take() else {
+                return plain(StatusCode::GONE, "Already streamed to another downloader".to_string());
+            };
+            builder
+                .body(Body::from_stream(deliver(stdin, share.clone())))
+                .unwrap()
+        }
+    }
+}
+
+/// Headers for a body of unknown length (no Content-Length, no ranges)
+fn streaming_headers(name: &str, inline: bool) -> axum::http::response::Builder {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type::from_name(name))
+        .header(header::CONTENT_DISPOSITION, content_type::content_disposition(inline, name))
+        .header(header::CACHE_CONTROL, "no-store")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+}
+
+/// Stream stdin to the one downloader. Once it's done - or the downloader
+/// goes away - there is nothing left to share, so the share shuts down.
+fn deliver(stdin: AsyncInput, share: Arc<StdinShare>) -> impl Stream<Item = io::Result<Bytes>> {
+    let delivery = Delivery {
+        share,
+        bytes: 0,
+        reported: Instant::now(),
+        finished: false,
+        failed: false,
+    };
+    stream::unfold(
+        (ReaderStream::with_capacity(stdin, CHUNK_SIZE), delivery),
+        |(mut reader, mut delivery)| async move {
+            if delivery.failed {
+                return None;
+            }
+            match reader.next().await {
+                Some(Ok(chunk)) => {
+                    delivery.advance(chunk.len() as u64);
+                    Some((Ok(chunk), (reader, delivery)))
+                }
+                Some(Err(e)) => {
+                    delivery.fail(format!("reading stdin failed: {}", e));
+                    Some((Err(e), (reader, delivery)))
+                }
+                None => {
+                    delivery.finish();
+                    None
+                }
+            }
+        },
+    )
+}
+
+struct Delivery {
+    share: Arc<StdinShare>,
+    bytes: u64,
+    reported: Instant,
+    /// Stdin reached EOF
+    finished: bool,
+    failed: bool,
+}
+
+impl Delivery {
+    fn advance(&mut self, len: u64) {
+        self.bytes += len;
+        if self.reported.elapsed() >= PROGRESS_INTERVAL {
+            self.reported = Instant::now();
+            let bytes = self.bytes;
+            self.share.state.update(|s| {
+                if let Some(progress) = &mut s.stdin {
+                    progress.bytes = bytes;
+                }
+            });
+        }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+        self.end(format!("stdin delivered ({} bytes)", self.bytes));
+    }
+
+    fn fail(&mut self, reason: String) {
+        self.failed = true;
+        self.end(reason);
+    }
+
+    fn end(&self, reason: String) {
+        let (bytes, finished) = (self.bytes, self.finished);
+        self.share.state.update(|s| {
+            if let Some(progress) = &mut s.stdin {
+                progress.bytes = bytes;
+                progress.finished = finished;
+            }
+            s.ended_reason.get_or_insert(reason);
+        });
+        self.share.shutdown.cancel();
+    }
+}
+
+impl Drop for Delivery {
+    fn drop(&mut self) {
+        if !self.finished && !self.failed {
+            self.end(format!("downloader disconnected after {} bytes of stdin", self.bytes));
+        }
+    }
+}
+
+/// Copy stdin into the spool file on a blocking thread, publishing progress.
+/// Going over the limit ends the share rather than serving a truncated file.
+fn spawn_spooler(
+    mut stdin: Box<dyn Read + Send>,
+    path: PathBuf,
+    limit: u64,
+    progress: watch::Sender<Spooled>,
+    state: StateHandle,
+    shutdown: CancellationToken,
+) {
+    tokio::task::spawn_blocking(move || {
+        let report = |bytes: u64, finished: bool| {
+            state.update(|s| {
+                if let Some(stdin) = &mut s.stdin {
+                    stdin.bytes = bytes;
+                    stdin.finished = finished;
+                }
+            })
+        };
+
+        let result = (|| -> io::Result<u64> {
+            let mut file = std::fs::File::create(&path)?;
+            let mut buf = vec![0; CHUNK_SIZE];
+            let mut total = 0u64;
+            let mut reported = Instant::now();
+            loop {
+                let n = match stdin.read(&mut buf) {
+                    Ok(0) => return Ok(total),
+                    Ok(n) => n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                total += n as u64;
+                if total > limit {
+                    return Err(io::Error::other(format!(
+                        "stdin is larger than the --spool limit of {} bytes",
+                        limit
+                    )));
+                }
+                file.write_all(&buf[..n])?;
+                progress.send_replace(Spooled::Reading(total));
+                if reported.elapsed() >= PROGRESS_INTERVAL {
+                    reported = Instant::now();
+                    report(total, false);
+                }
+            }
+        })();
+
+        match result {
+            Ok(total) => {
+                progress.send_replace(Spooled::Done(total));
+                report(total, true);
+            }
+            Err(e) => {
+                progress.send_replace(Spooled::Failed(e.to_string()));
+                state.update(|s| {
+                    s.ended_reason.get_or_insert(e.to_string());
+                });
+                shutdown.cancel();
+            }
+        }
+    });
+}
+
+/// Read the spool file as it grows, finishing once stdin has been fully read
+fn follow_spool(path: PathBuf, progress: watch::Receiver<Spooled>) -> impl Stream<Item = io::Result<Bytes>> {
+    struct Follow {
+        path: PathBuf,
+        file: Option<File>,
+        offset: u64,
+        progress: watch::Receiver<Spooled>,
+        done: bool,
+    }
+
+    let follow = Follow {
+        path,
+        file: None,
+        offset: 0,
+        progress,
+        done: false,
+    };
+    stream::unfold(follow, |mut follow| async move {
+        if follow.done {
+            return None;
+        }
+        loop {
+            let spooled = follow.progress.borrow_and_update().clone();
+            let (available, complete) = match spooled {
+                Spooled::Reading(n) => (n, false),
+                Spooled::Done(n) => (n, true),
+                Spooled::Failed(reason) => {
+                    follow.done = true;
+                    return Some((Err(io::Error::other(reason)), follow));
+                }
+            };
+
+            if follow.offset < available {
+                let file = match &mut follow.file {
+                    Some(file) => file,
+                    None => match File::open(&follow.path).await {
+                        Ok(file) => follow.file.insert(file),
+                        Err(e) => {
+                            follow.done = true;
+                            return Some((Err(e), follow));
+                        }
+                    },
+                };
+                let want = (available - follow.offset).min(CHUNK_SIZE as u64) as usize;
+                let mut buf = vec![0; want];
+                return match file.read_exact(&mut buf).await {
+                    Ok(_) => {
+                        follow.offset += want as u64;
+                        Some((Ok(Bytes::from(buf)), follow))
+                    }
+                    Err(e) => {
+                        follow.done = true;
+                        Some((Err(e), follow))
+                    }
+                };
+            }
+            if complete {
+                return None;
+            }
+            // Spooler gone without finishing - the daemon is going down
+            if follow.progress.changed().await.is_err() {
+                follow.done = true;
+                return Some((Err(io::Error::other("stdin spool ended early")), follow));
+            }
+        }
+    })
+}
+
+fn plain(status: StatusCode, message: String) -> Response {
+    Response::builder()
+        .status(status)
+        .body(Body::from(message))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::TunnelState;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn handle() -> StateHandle {
+        StateHandle::in_memory(TunnelState::new("test".to_string(), 0, 0, "dump.sql".to_string()))
+    }
+
+    async fn send(app: &Router, method: Method) -> (StatusCode, String) {
+        let req = Request::builder().method(method).uri("/dump.sql").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    fn ended_reason(state: &StateHandle) -> Option<String> {
+        let mut reason = None;
+        state.update(|s| reason = s.ended_reason.clone());
+        reason
+    }
+
+    #[tokio::test]
+    async fn streams_to_a_single_downloader() {
+        let (state, shutdown) = (handle(), CancellationToken::new());
+        let input = Input::Stream(Box::new(io::Cursor::new(b"SELECT 1;".to_vec())));
+        let app = share("dump.sql".to_string(), input, state.clone(), shutdown.clone()).unwrap();
+
+        // Looking doesn't use up the only copy
+        assert_eq!(send(&app, Method::HEAD).await, (StatusCode::OK, String::new()));
+        assert!(!shutdown.is_cancelled());
+
+        assert_eq!(send(&app, Method::GET).await, (StatusCode::OK, "SELECT 1;".to_string()));
+        assert!(shutdown.is_cancelled());
+        assert_eq!(ended_reason(&state).as_deref(), Some("stdin delivered (9 bytes)"));
+
+        let (status, _) = send(&app, Method::GET).await;
+        assert_eq!(status, StatusCode::GONE);
+    }
+
+    #[tokio::test]
+    async fn spooling_past_the_limit_ends_the_share() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (state, shutdown) = (handle(), CancellationToken::new());
+        let input = Input::Spool {
+            reader: Box::new(io::Cursor::new(vec![b'x'; 100])),
+            path: tmp.path().join("spool").join("dump.sql"),
+            limit: 10,
+        };
+        let app = share("dump.sql".to_string(), input, state.clone(), shutdown.clone()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), shutdown.cancelled()).await.unwrap();
+        let reason = ended_reason(&state).unwrap();
+        assert!(reason.contains("--spool limit of 10 bytes"), "{}", reason);
+        assert_eq!(send(&app, Method::GET).await, (StatusCode::GONE, reason));
+    }
+
+    #[tokio::test]
+    async fn follows_a_growing_spool() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("dump.sql");
+        std::fs::write(&path, "first ").unwrap();
+        let (tx, progress) = watch::channel(Spooled::Reading(6));
+        let mut follow = Box::pin(follow_spool(path.clone(), progress));
+
+        assert_eq!(follow.next().await.unwrap().unwrap(), "first ");
+        // Nothing new yet: the reader waits rather than ending early
+        let waiting = tokio::time::timeout(Duration::from_millis(50), follow.next()).await;
+        assert!(waiting.is_err());
+
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"second").unwrap();
+        tx.send_replace(Spooled::Done(12));
+        assert_eq!(follow.next().await.unwrap().unwrap(), "second");
+        assert!(follow.next().await.is_none());
+
+        // A spooler that goes away mid-read is an error, not a short file
+        let (tx, progress) = watch::channel(Spooled::Reading(0));
+        let mut follow = Box::pin(follow_spool(path, progress));
+        drop(tx);
+        assert!(follow.next().await.unwrap().is_err());
+    }
+}
@@ -0,0 +1,574 @@
+// ⏯️ tus 1.0 resumable uploads for drop-box mode: partial uploads live under
+// ~/.yeet/uploads so they survive dropped connections, tunnel and daemon restarts
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use axum::{
+    body::Body,
+    extract::{OriginalUri, Path as UrlPath, Request, State},
+    http::{header, response::Builder, HeaderMap, StatusCode},
+    response::Response,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use super::{auth::random_token, files::http_date, upload::Receiver};
+
+const TUS_VERSION: &str = "1.0.0";
+const TUS_EXTENSIONS: &str = "creation,creation-with-upload,termination,expiration";
+const OFFSET_CONTENT_TYPE: &str = "application/offset+octet-stream";
+/// Unfinished uploads are dropped after this long without a PATCH
+const EXPIRE_AFTER: Duration = Duration::from_secs(24 * 3600);
+
+/// Partial uploads on disk: `<id>.json` (what) next to `<id>.part` (bytes so far)
+pub struct Store {
+    dir: PathBuf,
+    /// Uploads with a PATCH in flight; a second concurrent PATCH is refused
+    busy: Mutex<HashSet<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UploadInfo {
+    length: u64,
+    /// Raw Upload-Metadata header, echoed back on HEAD
+    metadata: Option<String>,
+    filename: String,
+    /// Drop-box directory the upload belongs to
+    target: PathBuf,
+}
+
+impl Store {
+    pub fn new(dir: PathBuf) -> Self {
+        let store = Self {
+            dir,
+            busy: Mutex::new(HashSet::new()),
+        };
+        store.sweep_expired();
+        store
+    }
+
+    fn info_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn part_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.part", id))
+    }
+
+    /// Info for an upload of this drop box; ids are our own hex tokens
+    fn load(&self, id: &str, target: &Path) -> Option<UploadInfo> {
+        if id.is_empty() || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let info: UploadInfo = serde_json::from_str(&fs::read_to_string(self.info_path(id)).ok()?).ok()?;
+        (info.target == target).then_some(info)
+    }
+
+    fn offset(&self, id: &str) -> u64 {
+        fs::metadata(self.part_path(id)).map(|m| m.len()).unwrap_or(0)
+    }
+
+    /// Last activity plus EXPIRE_AFTER
+    fn expires(&self, id: &str) -> SystemTime {
+        fs::metadata(self.part_path(id))
+            .and_then(|m| m.modified())
+            .unwrap_or_else(|_| SystemTime::now())
+            + EXPIRE_AFTER
+    }
+
+    fn remove(&self, id: &str) {
+        let _ = fs::remove_file(self.part_path(id));
+        let _ = fs::remove_file(self.info_path(id));
+    }
+
+    fn sweep_expired(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                    if !self.part_path(id).exists() || self.expires(id) <= SystemTime::now() {
+                        self.remove(id);
+                    }
+                }
+            }
+        }
+    }
+
+    fn lock(&self, id: &str) -> Option<BusyGuard<'_>> {
+        self.busy
+            .lock()
+            .unwrap()
+            .insert(id.to_string())
+            .then(|| BusyGuard { store: self, id: id.to_string() })
+    }
+}
+
+struct BusyGuard<'a> {
+    store: &'a Store,
+    id: String,
+}
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.store.busy.lock().unwrap().remove(&self.id);
+    }
+}
+
+fn tus_response(status: StatusCode) -> Builder {
+    Response::builder()
+        .status(status)
+        .header("Tus-Resumable", TUS_VERSION)
+        .header(header::CACHE_CONTROL, "no-store")
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    tus_response(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(message.to_string()))
+        .unwrap()
+}
+
+/// Every request except OPTIONS must say which protocol version it speaks
+fn check_version(headers: &HeaderMap) -> Option<Response> {
+    match headers.get("Tus-Resumable").and_then(|v| v.to_str().ok()) {
+        Some(TUS_VERSION) => None,
+        _ => Some(
+            tus_response(StatusCode::PRECONDITION_FAILED)
+                .header("Tus-Version", TUS_VERSION)
+                .body(Body::empty())
+                .unwrap(),
+        ),
+    }
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// `filename` from `Upload-Metadata: key base64value,key2 base64value2`
+fn metadata_filename(metadata: &str) -> Option<String> {
+    metadata.split(',').find_map(|pair| {
+        let mut parts = pair.trim().splitn(2, ' ');
+        let key = parts.next()?;
+        let value = parts.next().unwrap_or_default();
+        (key == "filename" || key == "name")
+            .then(|| STANDARD.decode(value.trim()).ok())
+            .flatten()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    })
+}
+
+/// `OPTIONS /tus` - server capabilities
+pub async fn options(State(receiver): State<Arc<Receiver>>) -> Response {
+    tus_response(StatusCode::NO_CONTENT)
+        .header("Tus-Version", TUS_VERSION)
+        .header("Tus-Extension", TUS_EXTENSIONS)
+        .header("Tus-Max-Size", receiver.config().max_size)
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// `POST /tus` - creation (optionally with the first chunk in the body)
+pub async fn create(
+    State(receiver): State<Arc<Receiver>>,
+    OriginalUri(original): OriginalUri,
+    req: Request,
+) -> Response {
+    if let Some(response) = check_version(req.headers()) {
+        return response;
+    }
+    let store = receiver.tus();
+    store.sweep_expired();
+
+    let Some(length) = header_u64(req.headers(), "Upload-Length") else {
+        return error(StatusCode::BAD_REQUEST, "Upload-Length required");
+    };
+    if length > receiver.config().max_size {
+        return error(StatusCode::PAYLOAD_TOO_LARGE, "Upload exceeds the size limit");
+    }
+    let metadata = req
+        .headers()
+        .get("Upload-Metadata")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let Some(filename) = metadata
+        .as_deref()
+        .and_then(metadata_filename)
+        .and_then(|name| super::upload::sanitize_filename(&name))
+    else {
+        return error(StatusCode::BAD_REQUEST, "Upload-Metadata must carry a valid filename");
+    };
+
+    let id = random_token();
+    let info = UploadInfo {
+        length,
+        metadata,
+        filename,
+        target: receiver.config().dir.clone(),
+    };
+    let created = fs::write(store.part_path(&id), b"").and_then(|_| {
+        fs::write(store.info_path(&id), serde_json::to_string(&info).map_err(io::Error::other)?)
+    });
+    if created.is_err() {
+        store.remove(&id);
+        return error(StatusCode::INTERNAL_SERVER_ERROR, "Could not create upload");
+    }
+
+    // Location keeps any secret-link prefix the client came in through
+    let location = format!("{}/{}", original.path().trim_end_matches('/'), id);
+
+    let has_body = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes() == OFFSET_CONTENT_TYPE.as_bytes());
+    // An empty file is complete as soon as it exists
+    let mut response = if has_body || length == 0 {
+        append(&receiver, &id, info, 0, req).await
+    } else {
+        tus_response(StatusCode::CREATED)
+            .header("Upload-Offset", 0)
+            .header("Upload-Expires", http_date(store.expires(&id)))
+            .body(Body::empty())
+            .unwrap()
+    };
+    if response.status().is_success() {
+        *response.status_mut() = StatusCode::CREATED;
+        if let Ok(value) = location.parse() {
+            response.headers_mut().insert(header::LOCATION, value);
+        }
+    }
+    response
+}
+
+/// `HEAD /tus/<id>` - how much the server already has
+pub async fn head(
+    State(receiver): State<Arc<Receiver>>,
+    UrlPath(id): UrlPath<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(response) = check_version(&headers) {
+        return response;
+    }
+    let store = receiver.tus();
+    let Some(info) = store.load(&id, &receiver.config().dir) else {
+        return error(StatusCode::NOT_FOUND, "");
+    };
+    if store.expires(&id) <= SystemTime::now() {
+        store.remove(&id);
+        return error(StatusCode::GONE, "");
+    }
+
+    let mut builder = tus_response(StatusCode::OK)
+        .header("Upload-Offset", store.offset(&id))
+        .header("Upload-Length", info.length)
+        .header("Upload-Expires", http_date(store.expires(&id)));
+    if let Some(metadata) = &info.metadata {
+        builder = builder.header("Upload-Metadata", metadata);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+/// `PATCH /tus/<id>` - append a chunk at the current offset
+pub async fn patch(
+    State(receiver): State<Arc<Receiver>>,
+    UrlPath(id): UrlPath<String>,
+    req: Request,
+) -> Response {
+    if let Some(response) = check_version(req.headers()) {
+        return response;
+    }
+    let is_offset_stream = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes() == OFFSET_CONTENT_TYPE.as_bytes());
+    if !is_offset_stream {
+        return error(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Content-Type must be application/offset+octet-stream");
+    }
+    let Some(offset) = header_u64(req.headers(), "Upload-Offset") else {
+        return error(StatusCode::BAD_REQUEST, "Upload-Offset required");
+    };
+
+    let store = receiver.tus();
+    let Some(info) = store.load(&id, &receiver.config().dir) else {
+        return error(StatusCode::NOT_FOUND, "");
+    };
+    if store.expires(&id) <= SystemTime::now() {
+        store.remove(&id);
+        return error(StatusCode::GONE, "");
+    }
+    append(&receiver, &id, info, offset, req).await
+}
+
+/// Shared by PATCH and creation-with-upload
+async fn append(receiver: &Receiver, id: &str, info: UploadInfo, offset: u64, req: Request) -> Response {
+    let store = receiver.tus();
+    let Some(_guard) = store.lock(id) else {
+        return error(StatusCode::CONFLICT, "Another request is writing to this upload");
+    };
+    let current = store.offset(id);
+    if offset != current {
+        return tus_response(StatusCode::CONFLICT)
+            .header("Upload-Offset", current)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    let mut file = match tokio::fs::OpenOptions::new().append(true).open(store.part_path(id)).await {
+        Ok(file) => file,
+        Err(_) => return error(StatusCode::INTERNAL_SERVER_ERROR, "Could not open upload"),
+    };
+
+    // Keep whatever arrives even if the connection drops: that's the point of tus
+    let mut written = current;
+    let mut stream = req.into_body().into_data_stream();
+    let mut failure = None;
+    while let Some(chunk) = stream.next().await {
+        let Ok(chunk) = chunk else {
+            break;
+        };
+        if written + chunk.len() as u64 > info.length {
+            failure = Some(error(StatusCode::BAD_REQUEST, "Chunk runs past Upload-Length"));
+            break;
+        }
+        if file.write_all(&chunk).await.is_err() {
+            failure = Some(error(StatusCode::INTERNAL_SERVER_ERROR, "Could not write upload"));
+            break;
+        }
+        written += chunk.len() as u64;
+    }
+    let _ = file.sync_data().await;
+    drop(file);
+    if let Some(response) = failure {
+        return response;
+    }
+
+    if written == info.length {
+        if let Err(e) = complete(receiver, id, &info).await {
+            return error(StatusCode::INTERNAL_SERVER_ERROR, &format!("Could not save file: {}", e));
+        }
+    }
+
+    tus_response(StatusCode::NO_CONTENT)
+        .header("Upload-Offset", written)
+        .header("Upload-Expires", http_date(store.expires(id)))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Move a finished upload into the drop box
+async fn complete(receiver: &Receiver, id: &str, info: &UploadInfo) -> io::Result<()> {
+    let store = receiver.tus();
+    let part = store.part_path(id);
+    let staged = info
+        .target
+        .join(format!(".{}.yeet-part-{}", info.filename, random_token()));
+
+    // ~/.yeet may be on another filesystem than the drop box
+    let staged_clone = staged.clone();
+    tokio::task::spawn_blocking(move || match fs::rename(&part, &staged_clone) {
+        Ok(()) => Ok(()),
+        Err(_) => fs::copy(&part, &staged_clone).and_then(|_| fs::remove_file(&part)),
+    })
+    .await
+    .map_err(io::Error::other)??;
+
+    let result = receiver.finish(&staged, &info.filename, info.length);
+    if result.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    store.remove(id);
+    result.map(|_| ())
+}
+
+/// `DELETE /tus/<id>` - termination
+pub async fn terminate(
+    State(receiver): State<Arc<Receiver>>,
+    UrlPath(id): UrlPath<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(response) = check_version(&headers) {
+        return response;
+    }
+    let store = receiver.tus();
+    if store.load(&id, &receiver.config().dir).is_none() {
+        return error(StatusCode::NOT_FOUND, "");
+    }
+    let Some(_guard) = store.lock(&id) else {
+        return error(StatusCode::CONFLICT, "Upload is being written");
+    };
+    store.remove(&id);
+    tus_response(StatusCode::NO_CONTENT).body(Body::empty()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::upload::UploadConfig;
+
+    fn receiver(tmp: &Path) -> Arc<Receiver> {
+        let dir = tmp.join("inbox");
+        fs::create_dir_all(&dir).unwrap();
+        fs::create_dir_all(tmp.join("tus")).unwrap();
+        let config = UploadConfig {
+            dir,
+            max_size: 1 << 20,
+            overwrite: false,
+        };
+        Arc::new(Receiver::for_tests(config, tmp.join("tus")))
+    }
+
+    fn request(method: &str, headers: &[(&str, &str)], body: &'static str) -> Request {
+        let mut builder = Request::builder().method(method).uri("/tus").header("Tus-Resumable", TUS_VERSION);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::from(body)).unwrap()
+    }
+
+    fn offset_of(response: &Response) -> Option<u64> {
+        response.headers().get("Upload-Offset")?.to_str().ok()?.parse().ok()
+    }
+
+    /// POST an upload of `length` bytes called `name`; returns its id
+    async fn start(receiver: &Arc<Receiver>, length: &str, name: &str) -> String {
+        let metadata = format!("filename {}", STANDARD.encode(name));
+        let req = request("POST", &[("Upload-Length", length), ("Upload-Metadata", &metadata)], "");
+        let response = create(State(receiver.clone()), OriginalUri("/key/tus/".parse().unwrap()), req).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(offset_of(&response), Some(0));
+        let location = response.headers()[header::LOCATION].to_str().unwrap();
+        // The secret-link prefix the client used survives into Location
+        location.strip_prefix("/key/tus/").unwrap().to_string()
+    }
+
+    async fn send(receiver: &Arc<Receiver>, id: &str, offset: &str, chunk: &'static str) -> Response {
+        let req = request(
+            "PATCH",
+            &[("Upload-Offset", offset), ("Content-Type", OFFSET_CONTENT_TYPE)],
+            chunk,
+        );
+        patch(State(receiver.clone()), UrlPath(id.to_string()), req).await
+    }
+
+    async fn probe(receiver: &Arc<Receiver>, id: &str) -> Response {
+        let req = request("HEAD", &[], "");
+        head(State(receiver.clone()), UrlPath(id.to_string()), req.headers().clone()).await
+    }
+
+    #[tokio::test]
+    async fn resumes_an_upload_chunk_by_chunk() {
+        let tmp = tempfile::tempdir().unwrap();
+        let receiver = receiver(tmp.path());
+        let id = start(&receiver, "10", "hi.txt").await;
+
+        let response = probe(&receiver, &id).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(offset_of(&response), Some(0));
+        assert_eq!(response.headers()["Upload-Length"], "10");
+
+        let response = send(&receiver, &id, "0", "hello").await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(offset_of(&response), Some(5));
+        assert_eq!(offset_of(&probe(&receiver, &id).await), Some(5));
+
+        // A replayed chunk at a stale offset is refused, and told where to resume
+        let response = send(&receiver, &id, "0", "hello").await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(offset_of(&response), Some(5));
+
+        // More bytes than Upload-Length announced
+        let response = send(&receiver, &id, "5", "world!!").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(offset_of(&probe(&receiver, &id).await), Some(5));
+
+        let response = send(&receiver, &id, "5", "world").await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(offset_of(&response), Some(10));
+        let inbox = &receiver.config().dir;
+        assert_eq!(fs::read_to_string(inbox.join("hi.txt")).unwrap(), "helloworld");
+        assert_eq!(fs::read_dir(inbox).unwrap().count(), 1);
+
+        // Finished uploads are no longer resumable
+        assert_eq!(probe(&receiver, &id).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn refuses_bad_creations() {
+        let tmp = tempfile::tempdir().unwrap();
+        let receiver = receiver(tmp.path());
+        let uri = || OriginalUri("/tus".parse().unwrap());
+        let named = format!("filename {}", STANDARD.encode("a.txt"));
+
+        let req = Request::builder().method("POST").uri("/tus").body(Body::empty()).unwrap();
+        let response = create(State(receiver.clone()), uri(), req).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let req = request("POST", &[("Upload-Metadata", &named)], "");
+        let response = create(State(receiver.clone()), uri(), req).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let req = request("POST", &[("Upload-Length", "2097152"), ("Upload-Metadata", &named)], "");
+        let response = create(State(receiver.clone()), uri(), req).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let dots = format!("filename {}", STANDARD.encode(".."));
+        let req = request("POST", &[("Upload-Length", "1"), ("Upload-Metadata", &dots)], "");
+        let response = create(State(receiver.clone()), uri(), req).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        assert_eq!(fs::read_dir(tmp.path().join("tus")).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn terminates_an_upload() {
+        let tmp = tempfile::tempdir().unwrap();
+        let receiver = receiver(tmp.path());
+        let id = start(&receiver, "10", "gone.txt").await;
+        assert_eq!(send(&receiver, &id, "0", "half").await.status(), StatusCode::NO_CONTENT);
+
+        let req = request("DELETE", &[], "");
+        let response = terminate(State(receiver.clone()), UrlPath(id.clone()), req.headers().clone()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(probe(&receiver, &id).await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(send(&receiver, &id, "4", "rest").await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(fs::read_dir(tmp.path().join("tus")).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(&receiver.config().dir).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn drops_expired_uploads() {
+        let tmp = tempfile::tempdir().unwrap();
+        let receiver = receiver(tmp.path());
+        let store = receiver.tus();
+        let age = |id: &str| {
+            let part = fs::File::options().write(true).open(store.part_path(id)).unwrap();
+            part.set_modified(SystemTime::now() - EXPIRE_AFTER - Duration::from_secs(60)).unwrap();
+        };
+
+        let stale = start(&receiver, "10", "stale.txt").await;
+        age(&stale);
+        let response = probe(&receiver, &stale).await;
+        assert_eq!(response.status(), StatusCode::GONE);
+        assert!(!store.info_path(&stale).exists());
+        assert!(!store.part_path(&stale).exists());
+
+        // Abandoned uploads nobody asks about again are swept on the next creation
+        let abandoned = start(&receiver, "10", "abandoned.txt").await;
+        let fresh = start(&receiver, "10", "fresh.txt").await;
+        age(&abandoned);
+        start(&receiver, "10", "next.txt").await;
+        assert!(!store.info_path(&abandoned).exists());
+        assert!(!store.part_path(&abandoned).exists());
+        assert_eq!(probe(&receiver, &fresh).await.status(), StatusCode::OK);
+    }
+}
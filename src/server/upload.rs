@@ -0,0 +1,548 @@
+// 📥 Drop-box mode (--receive): collaborators upload files through the tunnel
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Multipart, Path as UrlPath, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+    routing::{get, put},
+    Router,
+};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+
+use super::{assets, auth::random_token, tus};
+use crate::state::{unix_now, uploads_dir, ReceivedFile, StateHandle};
+
+/// Longest file name we create, in bytes (most filesystems allow 255)
+const MAX_NAME_LEN: usize = 200;
+/// How many "name (n).ext" variants are tried before giving up
+const MAX_RENAME_ATTEMPTS: u32 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct UploadConfig {
+    pub dir: PathBuf,
+    /// Largest single upload in bytes
+    pub max_size: u64,
+    /// Replace existing files instead of picking a new name
+    pub overwrite: bool,
+}
+
+/// Shared state of the upload routes
+pub struct Receiver {
+    config: UploadConfig,
+    state: StateHandle,
+    tus: tus::Store,
+}
+
+#[derive(Debug)]
+enum UploadError {
+    BadName,
+    TooLarge(u64),
+    Client(String),
+    Io(io::Error),
+}
+
+impl From<io::Error> for UploadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl UploadError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            Self::BadName => (StatusCode::BAD_REQUEST, "Invalid file name".to_string()),
+            Self::TooLarge(max) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("File exceeds the upload limit of {} bytes", max),
+            ),
+            Self::Client(e) => (StatusCode::BAD_REQUEST, format!("Upload interrupted: {}", e)),
+            Self::Io(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Could not save file: {}", e)),
+        };
+        Response::builder()
+            .status(status)
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(Body::from(message))
+            .unwrap()
+    }
+}
+
+#[derive(Serialize)]
+struct Saved {
+    name: String,
+    size: u64,
+}
+
+/// Routes for receive mode: the upload page, multipart POST, raw PUT and tus
+pub fn router<S: Clone + Send + Sync + 'static>(config: UploadConfig, state: StateHandle) -> Router<S> {
+    let receiver = Receiver {
+        config,
+        state,
+        tus: tus::Store::new(uploads_dir()),
+    };
+    Router::new()
+        .route("/", get(page))
+        .route("/upload", axum::routing::post(multipart_upload))
+        .route("/upload/:name", put(raw_upload))
+        .route("/tus", axum::routing::options(tus::options).post(tus::create))
+        .route("/tus/", axum::routing::options(tus::options).post(tus::create))
+        .route(
+            "/tus/:id",
+            axum::routing::head(tus::head).patch(tus::patch).delete(tus::terminate),
+        )
+        // Uploads are streamed to disk and capped by --max-upload-size instead
+        .layer(DefaultBodyLimit::disable())
+        .with_state(Arc::new(receiver))
+}
+
+/// Parse sizes like "500M", "2G", "1.5GB" or plain bytes
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}' (e.g. 500M, 2G)", value))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("unknown size unit '{}' (use K, M, G or T)", unit)),
+    };
+    let bytes = (number * multiplier as f64) as u64;
+    if bytes == 0 {
+        return Err("size must be greater than zero".to_string());
+    }
+    Ok(bytes)
+}
+
+/// Reduce a client-supplied name to a single harmless path component
+pub fn sanitize_filename(raw: &str) -> Option<String> {
+    let base = raw.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|c| match c {
+            c if c.is_control() => '_',
+            '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            c => c,
+        })
+        .collect();
+    // No dotfiles (and so no "." or ".."), no trailing dots/spaces (Windows)
+    let cleaned = cleaned.trim().trim_start_matches('.').trim_end_matches(['.', ' ']);
+
+    let mut name = String::new();
+    for c in cleaned.chars() {
+        if name.len() + c.len_utf8() > MAX_NAME_LEN {
+            break;
+        }
+        name.push(c);
+    }
+    (!name.is_empty()).then_some(name)
+}
+
+async fn page() -> Response {
+    let page = UPLOAD_PAGE
+        .replace("{css}", &assets::url("yeet.css"))
+        .replace("{js}", &assets::url("upload.js"));
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::CONTENT_SECURITY_POLICY, assets::CSP)
+        .body(Body::from(page))
+        .unwrap()
+}
+
+/// `PUT /upload/<name>` with the raw file as the body
+async fn raw_upload(
+    State(receiver): State<Arc<Receiver>>,
+    UrlPath(name): UrlPath<String>,
+    req: Request,
+) -> Response {
+    if let Some(len) = content_length(req.headers()) {
+        if len > receiver.config.max_size {
+            return UploadError::TooLarge(receiver.config.max_size).into_response();
+        }
+    }
+
+    match receiver.save(&name, req.into_body().into_data_stream()).await {
+        Ok(saved) => json_response(StatusCode::CREATED, &saved),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// `POST /upload` as multipart/form-data; every file field is saved
+async fn multipart_upload(
+    State(receiver): State<Arc<Receiver>>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Response {
+    let mut saved = Vec::new();
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return UploadError::Client(e.to_string()).into_response(),
+        };
+        let Some(name) = field.file_name().map(str::to_string) else {
+            continue;
+        };
+        if name.is_empty() {
+            // Form submitted without choosing a file
+            continue;
+        }
+        match receiver.save(&name, field).await {
+            Ok(file) => saved.push(file),
+            Err(e) => return e.into_response(),
+        }
+    }
+
+    let wants_html = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/html"));
+    if !wants_html {
+        return json_response(StatusCode::CREATED, &saved);
+    }
+
+    // Plain form post (no JavaScript): confirm and link back to the page
+    let items: String = saved
+        .iter()
+        .map(|f| format!("<li>{} ({} bytes)</li>", html_escape(&f.name), f.size))
+        .collect();
+    Response::builder()
+        .status(StatusCode::CREATED)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(Body::from(format!(
+            "<!DOCTYPE html><meta charset=\"UTF-8\"><title>YEET // received</title>\
+             <body style=\"background:#0a0e27;color:#00ff9f;font-family:monospace;padding:2rem\">\
+             <h1>✓ Received {} file(s)</h1><ul>{}</ul><p><a style=\"color:#00d4ff\" href=\"./\">Send more</a></p>",
+            saved.len(),
+            items
+        )))
+        .unwrap()
+}
+
+impl Receiver {
+    /// Stream an upload into a hidden temp file, then move it into place
+    async fn save<S, E>(&self, raw_name: &str, stream: S) -> Result<Saved, UploadError>
+    where
+        S: Stream<Item = Result<Bytes, E>>,
+        E: Display,
+    {
+        let name = sanitize_filename(raw_name).ok_or(UploadError::BadName)?;
+        let tmp = self.config.dir.join(format!(".{}.yeet-part-{}", name, random_token()));
+
+        let result = self.write_part(&tmp, stream).await;
+        let size = match result {
+            Ok(size) => size,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&tmp).await;
+                return Err(e);
+            }
+        };
+
+        match self.finish(&tmp, &name, size) {
+            Ok(final_name) => Ok(Saved { name: final_name, size }),
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp);
+                Err(e.into())
+            }
+        }
+    }
+
+    pub(super) fn config(&self) -> &UploadConfig {
+        &self.config
+    }
+
+    pub(super) fn tus(&self) -> &tus::Store {
+        &self.tus
+    }
+
+    /// Move a complete temp file (inside the drop box) into place and record
+    /// it for the TUI; returns the name it was saved under
+    pub(super) fn finish(&self, tmp: &Path, name: &str, size: u64) -> io::Result<String> {
+        let final_name = self.place(tmp, name)?;
+        self.state.update(|s| s.record_upload(ReceivedFile {
+            name: final_name.clone(),
+            size,
+            at: unix_now(),
+        }));
+        Ok(final_name)
+    }
+
+    async fn write_part<S, E>(&self, tmp: &Path, stream: S) -> Result<u64, UploadError>
+    where
+        S: Stream<Item = Result<Bytes, E>>,
+        E: Display,
+    {
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(tmp)
+            .await?;
+        let mut written = 0u64;
+        futures_util::pin_mut!(stream);
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| UploadError::Client(e.to_string()))?;
+            written += chunk.len() as u64;
+            if written > self.config.max_size {
+                return Err(UploadError::TooLarge(self.config.max_size));
+            }
+            file.write_all(&chunk).await?;
+        }
+        file.sync_all().await?;
+        Ok(written)
+    }
+
+    /// Move the finished temp file to its final name. Without --overwrite an
+    /// existing file is never replaced: "a.txt" becomes "a (1).txt" and so on.
+    fn place(&self, tmp: &Path, name: &str) -> io::Result<String> {
+        if self.config.overwrite {
+            std::fs::rename(tmp, self.config.dir.join(name))?;
+            return Ok(name.to_string());
+        }
+
+        for attempt in 0..MAX_RENAME_ATTEMPTS {
+            let candidate = numbered_name(name, attempt);
+            // hard_link fails if the target exists, so two racing uploads can't clobber each other
+            match std::fs::hard_link(tmp, self.config.dir.join(&candidate)) {
+                Ok(()) => {
+                    std::fs::remove_file(tmp)?;
+                    return Ok(candidate);
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(_) => {
+                    // Filesystem without hard links: fall back to check-then-rename
+                    let target = self.config.dir.join(&candidate);
+                    if target.exists() {
+                        continue;
+                    }
+                    std::fs::rename(tmp, target)?;
+                    return Ok(candidate);
+                }
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::AlreadyExists, "too many files with this name"))
+    }
+}
+
+#[cfg(test)]
+impl Receiver {
+    /// Receiver whose partial uploads live in `tus_dir` and whose state stays in memory
+    pub(super) fn for_tests(config: UploadConfig, tus_dir: PathBuf) -> Self {
+        let state = crate::state::TunnelState::new("test".to_string(), 0, 0, config.dir.display().to_string());
+        Self {
+            config,
+            state: StateHandle::in_memory(state),
+            tus: tus::Store::new(tus_dir),
+        }
+    }
+}
+
+/// "report.pdf", 2 -> "report (2).pdf"
+fn numbered_name(name: &str, n: u32) -> String {
+    if n == 0 {
+        return name.to_string();
+    }
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{} ({}).{}", stem, n, ext),
+        _ => format!("{} ({})", name, n),
+    }
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers.get(header::CONTENT_LENGTH)?.to_str().ok()?.parse().ok()
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> Response {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_string(value).unwrap_or_default()))
+        .unwrap()
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const UPLOAD_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>YEET // DROP BOX</title>
+    <link href="{css}" rel="stylesheet">
+    <style>
+        * { margin: 0; padding: 0; box-sizing: border-box; }
+        body {
+            background: #0a0e27;
+            color: #00ff9f;
+            font-family: 'DejaVu Sans Mono', monospace;
+            padding: 2rem;
+            min-height: 100vh;
+        }
+        .container { max-width: 900px; margin: 0 auto; }
+        .header {
+            border: 2px solid #00ff9f;
+            padding: 1.5rem;
+            margin-bottom: 2rem;
+            background: rgba(0, 255, 159, 0.05);
+        }
+        .logo {
+            font-size: 2rem;
+            font-weight: bold;
+            color: #00d4ff;
+            text-shadow: 0 0 10px #00d4ff;
+            margin-bottom: 0.5rem;
+        }
+        .subtitle { color: #ff00ff; font-size: 0.9rem; }
+        .drop {
+            border: 2px dashed #ff00ff;
+            padding: 3rem 1rem;
+            text-align: center;
+            cursor: pointer;
+            background: rgba(255, 0, 255, 0.05);
+            color: #ff00ff;
+        }
+        .drop.over { background: rgba(255, 0, 255, 0.15); text-shadow: 0 0 5px #ff00ff; }
+        .drop input { display: none; }
+        noscript form { margin-top: 1rem; }
+        button, noscript input {
+            background: #1a1f3a;
+            border: 1px solid #00ff9f;
+            color: #00ff9f;
+            padding: 0.5rem 1rem;
+            font-family: 'DejaVu Sans Mono', monospace;
+        }
+        .item {
+            margin-top: 1rem;
+            padding: 0.75rem 1rem;
+            border: 1px solid rgba(0, 255, 159, 0.3);
+        }
+        .bar { height: 4px; background: rgba(0, 255, 159, 0.15); margin-top: 0.5rem; }
+        .bar div { height: 100%; width: 0; background: #00ff9f; }
+        .item.done { color: #00d4ff; }
+        .item.failed { color: #ff5555; border-color: #ff5555; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <div class="logo">▓▒░ YEET DROP BOX ░▒▓</div>
+            <div class="subtitle">▸ SEND FILES STRAIGHT TO THE OWNER OF THIS LINK</div>
+        </div>
+        <label class="drop" id="drop">
+            ⬆ DROP FILES HERE OR CLICK TO CHOOSE
+            <input type="file" id="picker" multiple>
+        </label>
+        <noscript>
+            <form method="post" action="upload" enctype="multipart/form-data">
+                <input type="file" name="file" multiple>
+                <button type="submit">UPLOAD</button>
+            </form>
+        </noscript>
+        <div id="list"></div>
+    </div>
+    <script src="{js}"></script>
+</body>
+</html>"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn sanitizes_client_file_names() {
+        assert_eq!(sanitize_filename("report.pdf").as_deref(), Some("report.pdf"));
+        assert_eq!(sanitize_filename("../../etc/passwd").as_deref(), Some("passwd"));
+        assert_eq!(sanitize_filename("/etc/shadow").as_deref(), Some("shadow"));
+        assert_eq!(sanitize_filename("..\\..\\boot.ini").as_deref(), Some("boot.ini"));
+        assert_eq!(sanitize_filename("C:\\Users\\me\\notes.txt").as_deref(), Some("notes.txt"));
+        assert_eq!(sanitize_filename(".bashrc").as_deref(), Some("bashrc"));
+        assert_eq!(sanitize_filename("..hidden").as_deref(), Some("hidden"));
+        assert_eq!(sanitize_filename("a<b>:c?.txt").as_deref(), Some("a_b__c_.txt"));
+        assert_eq!(sanitize_filename("line\nbreak").as_deref(), Some("line_break"));
+        assert_eq!(sanitize_filename("trailing. . ").as_deref(), Some("trailing"));
+
+        for nothing in ["", ".", "..", "dir/", "../", "...", "   "] {
+            assert_eq!(sanitize_filename(nothing), None, "{:?}", nothing);
+        }
+
+        let long = sanitize_filename(&"x".repeat(300)).unwrap();
+        assert_eq!(long.len(), MAX_NAME_LEN);
+        // Truncation never splits a multi-byte character
+        let wide = sanitize_filename(&"é".repeat(150)).unwrap();
+        assert!(wide.len() <= MAX_NAME_LEN);
+        assert_eq!(wide.chars().count(), MAX_NAME_LEN / 2);
+    }
+
+    #[test]
+    fn numbers_names_before_the_extension() {
+        assert_eq!(numbered_name("report.pdf", 0), "report.pdf");
+        assert_eq!(numbered_name("report.pdf", 2), "report (2).pdf");
+        assert_eq!(numbered_name("archive.tar.gz", 1), "archive.tar (1).gz");
+        assert_eq!(numbered_name("README", 3), "README (3)");
+    }
+
+    #[test]
+    fn place_never_overwrites_without_the_flag() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("inbox");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "original").unwrap();
+        let config = UploadConfig {
+            dir: dir.clone(),
+            max_size: 1 << 20,
+            overwrite: false,
+        };
+        let receiver = Receiver::for_tests(config.clone(), tmp.path().join("tus"));
+
+        for (content, expected) in [("first", "a (1).txt"), ("second", "a (2).txt")] {
+            let part = dir.join(".a.txt.yeet-part-test");
+            fs::write(&part, content).unwrap();
+            assert_eq!(receiver.place(&part, "a.txt").unwrap(), expected);
+            assert!(!part.exists());
+            assert_eq!(fs::read_to_string(dir.join(expected)).unwrap(), content);
+        }
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "original");
+
+        let receiver = Receiver::for_tests(UploadConfig { overwrite: true, ..config }, tmp.path().join("tus"));
+        let part = dir.join(".a.txt.yeet-part-test");
+        fs::write(&part, "replaced").unwrap();
+        assert_eq!(receiver.place(&part, "a.txt").unwrap(), "a.txt");
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "replaced");
+    }
+
+    #[test]
+    fn parses_human_sizes() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("10K"), Ok(10 << 10));
+        assert_eq!(parse_size("500M"), Ok(500 << 20));
+        assert_eq!(parse_size("500mb"), Ok(500 << 20));
+        assert_eq!(parse_size("2GiB"), Ok(2 << 30));
+        assert_eq!(parse_size("1.5G"), Ok(3 << 29));
+        assert_eq!(parse_size(" 1 T "), Ok(1 << 40));
+
+        for bad in ["", "G", "abc", "10X", "1.2.3M", "0", "0.0000001K", "-5M"] {
+            assert!(parse_size(bad).is_err(), "{:?}", bad);
+        }
+    }
+}
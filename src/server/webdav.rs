@@ -0,0 +1,603 @@
+// 🗄️ WebDAV (class 1) view of a shared directory at /dav, so it can be
+// mounted in a file manager. Read-only unless --webdav-write is given.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use axum::{
+    body::Body,
+    extract::{DefaultBodyLimit, OriginalUri, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+    routing::any,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use percent_encoding::percent_decode_str;
+use tokio::io::AsyncWriteExt;
+
+use super::{
+    auth::random_token,
+    files::{self, http_date, Validators},
+    paths::{encode_segment, PathError, SafeRoot},
+};
+
+/// Where the WebDAV tree is mounted
+const MOUNT: &str = "/dav";
+
+#[derive(Debug, Clone)]
+pub struct WebDavConfig {
+    /// Allow PUT, MKCOL, DELETE, COPY and MOVE
+    pub writable: bool,
+    /// Largest single PUT in bytes, the same cap as --receive uploads
+    pub max_size: u64,
+}
+
+struct WebDav {
+    root: SafeRoot,
+    config: WebDavConfig,
+}
+
+/// Routes serving the WebDAV tree under `/dav`
+pub fn router<S: Clone + Send + Sync + 'static>(root: SafeRoot, config: WebDavConfig) -> Router<S> {
+    Router::new()
+        .route(MOUNT, any(handle))
+        .route("/dav/", any(handle))
+        .route("/dav/*path", any(handle))
+        // PUT bodies are streamed to disk and capped by --max-upload-size instead
+        .layer(DefaultBodyLimit::disable())
+        .with_state(Arc::new(WebDav { root, config }))
+}
+
+async fn handle(State(dav): State<Arc<WebDav>>, OriginalUri(original): OriginalUri, req: Request) -> Response {
+    let path = req.uri().path().strip_prefix(MOUNT).unwrap_or_default().to_string();
+    // The public prefix of the mount, including any secret-link segment
+    let mount = format!(
+        "{}{}",
+        original.path().strip_suffix(req.uri().path()).unwrap_or_default(),
+        MOUNT
+    );
+
+    let writable = dav.config.writable;
+    match req.method().as_str() {
+        "OPTIONS" => options(writable),
+        "GET" | "HEAD" => get(&dav, &path, &mount, req.headers()).await,
+        "PROPFIND" => propfind(dav.clone(), path, mount, req.headers()).await,
+        "PUT" | "MKCOL" | "DELETE" | "COPY" | "MOVE" if !writable => status(StatusCode::FORBIDDEN, "Read-only share"),
+        "PUT" => put(&dav, &path, req).await,
+        "MKCOL" => mkcol(&dav, &path, req).await,
+        "DELETE" => delete(&dav, &path).await,
+        "COPY" => transfer(dav.clone(), &path, &mount, req.headers(), true).await,
+        "MOVE" => transfer(dav.clone(), &path, &mount, req.headers(), false).await,
+        _ => Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .header(header::ALLOW, allowed(writable))
+            .body(Body::empty())
+            .unwrap(),
+    }
+}
+
+fn allowed(writable: bool) -> &'static str {
+    if writable {
+        "OPTIONS, GET, HEAD, PROPFIND, PUT, MKCOL, DELETE, COPY, MOVE"
+    } else {
+        "OPTIONS, GET, HEAD, PROPFIND"
+    }
+}
+
+fn options(writable: bool) -> Response {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("DAV", "1")
+        .header("MS-Author-Via", "DAV")
+        .header(header::ALLOW, allowed(writable))
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn status(code: StatusCode, message: &str) -> Response {
+    Response::builder()
+        .status(code)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(message.to_string()))
+        .unwrap()
+}
+
+fn path_error(e: PathError) -> Response {
+    match e {
+        PathError::NotFound => status(StatusCode::NOT_FOUND, "Not found"),
+        PathError::Forbidden => status(StatusCode::FORBIDDEN, "Forbidden"),
+    }
+}
+
+/// Files download as usual; collections redirect to the HTML listing
+async fn get(dav: &WebDav, path: &str, mount: &str, headers: &HeaderMap) -> Response {
+    let target = match dav.root.resolve(path) {
+        Ok(target) => target,
+        Err(e) => return path_error(e),
+    };
+    if tokio::fs::metadata(&target).await.is_ok_and(|m| m.is_file()) {
+        return files::serve_file(&target, headers, false).await;
+    }
+    let listing = format!("{}{}", mount.strip_suffix(MOUNT).unwrap_or_default(), path);
+    Response::builder()
+        .status(StatusCode::FOUND)
+        .header(header::LOCATION, if listing.is_empty() { "/".to_string() } else { listing })
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Depth 0 or 1 only; infinite depth would walk the whole tree per request
+async fn propfind(dav: Arc<WebDav>, path: String, mount: String, headers: &HeaderMap) -> Response {
+    let depth = headers
+        .get("Depth")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("1")
+        .to_string();
+    if depth.eq_ignore_ascii_case("infinity") {
+        return Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .header(header::CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(Body::from(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:error xmlns:D=\"DAV:\"><D:propfind-finite-depth/></D:error>",
+            ))
+            .unwrap();
+    }
+
+    let multistatus = tokio::task::spawn_blocking(move || multistatus(&dav, &path, &mount, depth != "0")).await;
+    match multistatus {
+        Ok(Ok(body)) => Response::builder()
+            .status(StatusCode::MULTI_STATUS)
+            .header(header::CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(Body::from(body))
+            .unwrap(),
+        Ok(Err(e)) => path_error(e),
+        Err(_) => status(StatusCode::INTERNAL_SERVER_ERROR, "Could not list collection"),
+    }
+}
+
+/// PROPFIND body for a resource and, with `children`, its direct members
+fn multistatus(dav: &WebDav, path: &str, mount: &str, children: bool) -> Result<String, PathError> {
+    let target = dav.root.resolve(path)?;
+    let metadata = fs::metadata(&target).map_err(|_| PathError::NotFound)?;
+
+    // Canonical href of the requested resource, segment by segment
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| encode_segment(&percent_decode_str(s).decode_utf8_lossy()))
+        .collect();
+    let mut href = format!("{}/{}", mount, segments.join("/"));
+    if metadata.is_dir() && !href.ends_with('/') {
+        href.push('/');
+    }
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut body = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:multistatus xmlns:D=\"DAV:\">\n");
+    body.push_str(&prop_response(&href, &name, &target, &metadata));
+
+    if metadata.is_dir() && children {
+        let mut entries: Vec<_> = fs::read_dir(&target).into_iter().flatten().flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            if !dav.root.permits(&entry.path()) {
+                continue;
+            }
+            let Ok(child) = fs::metadata(entry.path()) else {
+                continue;
+            };
+            let child_name = entry.file_name().to_string_lossy().to_string();
+            let mut child_href = format!("{}{}", href, encode_segment(&child_name));
+            if child.is_dir() {
+                child_href.push('/');
+            }
+            body.push_str(&prop_response(&child_href, &child_name, &entry.path(), &child));
+        }
+    }
+    body.push_str("</D:multistatus>\n");
+    Ok(body)
+}
+
+fn prop_response(href: &str, name: &str, path: &Path, metadata: &fs::Metadata) -> String {
+    let mut props = format!("<D:displayname>{}</D:displayname>", xml_escape(name));
+    if metadata.is_dir() {
+        props.push_str("<D:resourcetype><D:collection/></D:resourcetype>");
+    } else {
+        props.push_str(&format!(
+            "<D:resourcetype/><D:getcontentlength>{}</D:getcontentlength>\
+             <D:getcontenttype>{}</D:getcontenttype>\
+             <D:getetag>{}</D:getetag>",
+            metadata.len(),
+            // Extension only: sniffing every entry would read each file
+            xml_escape(mime_guess::from_path(name).first_or_octet_stream().essence_str()),
+            xml_escape(&Validators::for_file(path, metadata).etag)
+        ));
+    }
+    if let Ok(modified) = metadata.modified() {
+        props.push_str(&format!("<D:getlastmodified>{}</D:getlastmodified>", http_date(modified)));
+    }
+    if let Ok(created) = metadata.created() {
+        props.push_str(&format!("<D:creationdate>{}</D:creationdate>", rfc3339(created)));
+    }
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>{}</D:prop>\
+         <D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>\n",
+        xml_escape(href),
+        props
+    )
+}
+
+fn rfc3339(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Writable paths resolve their parent only; the entry itself isn't followed
+fn entry_error(e: PathError) -> Response {
+    match e {
+        // RFC 4918: missing intermediate collections are a conflict
+        PathError::NotFound => status(StatusCode::CONFLICT, "Parent collection does not exist"),
+        PathError::Forbidden => status(StatusCode::FORBIDDEN, "Forbidden"),
+    }
+}
+
+fn too_large(max_size: u64) -> Response {
+    status(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        &format!("File exceeds the upload limit of {} bytes", max_size),
+    )
+}
+
+async fn put(dav: &WebDav, path: &str, req: Request) -> Response {
+    let max_size = dav.config.max_size;
+    let declared = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > max_size) {
+        return too_large(max_size);
+    }
+
+    let target = match dav.root.resolve_entry(path) {
+        Ok(target) => target,
+        Err(e) => return entry_error(e),
+    };
+    if tokio::fs::metadata(&target).await.is_ok_and(|m| m.is_dir()) {
+        return status(StatusCode::METHOD_NOT_ALLOWED, "Cannot PUT over a collection");
+    }
+    let existed = tokio::fs::symlink_metadata(&target).await.is_ok();
+
+    // Write next to the target and rename, so readers never see half a file
+    let parent = target.parent().unwrap_or(Path::new("/"));
+    let tmp = parent.join(format!(".yeet-dav-{}", random_token()));
+    let result = async {
+        let mut file = tokio::fs::File::create(&tmp).await?;
+        let mut body = req.into_body().into_data_stream();
+        let mut written = 0u64;
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(io::Error::other)?;
+            written += chunk.len() as u64;
+            if written > max_size {
+                return Ok(false);
+            }
+            file.write_all(&chunk).await?;
+        }
+        file.sync_all().await?;
+        tokio::fs::rename(&tmp, &target).await.map(|_| true)
+    }
+    .await;
+
+    match result {
+        Ok(true) if existed => status(StatusCode::NO_CONTENT, ""),
+        Ok(true) => status(StatusCode::CREATED, ""),
+        Ok(false) => {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            too_large(max_size)
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            status(StatusCode::INTERNAL_SERVER_ERROR, &format!("Could not write file: {}", e))
+        }
+    }
+}
+
+async fn mkcol(dav: &WebDav, path: &str, req: Request) -> Response {
+    let target = match dav.root.resolve_entry(path) {
+        Ok(target) => target,
+        Err(e) => return entry_error(e),
+    };
+    let has_body = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v != "0");
+    if has_body {
+        return status(StatusCode::UNSUPPORTED_MEDIA_TYPE, "MKCOL bodies are not supported");
+    }
+    match tokio::fs::create_dir(&target).await {
+        Ok(()) => status(StatusCode::CREATED, ""),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => status(StatusCode::METHOD_NOT_ALLOWED, "Already exists"),
+        Err(e) => status(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+async fn delete(dav: &WebDav, path: &str) -> Response {
+    let target = match dav.root.resolve_entry(path) {
+        Ok(target) => target,
+        Err(e) => return entry_error(e),
+    };
+    if tokio::fs::symlink_metadata(&target).await.is_err() {
+        return status(StatusCode::NOT_FOUND, "Not found");
+    }
+    match remove(&target).await {
+        Ok(()) => status(StatusCode::NO_CONTENT, ""),
+        Err(e) => status(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+/// Symlinks are removed themselves, never what they point at
+async fn remove(target: &Path) -> io::Result<()> {
+    if tokio::fs::symlink_metadata(target).await?.is_dir() {
+        tokio::fs::remove_dir_all(target).await
+    } else {
+        tokio::fs::remove_file(target).await
+    }
+}
+
+/// COPY and MOVE; the Destination must name a path inside this share
+async fn transfer(dav: Arc<WebDav>, path: &str, mount: &str, headers: &HeaderMap, copy: bool) -> Response {
+    let source = match dav.root.resolve_entry(path) {
+        Ok(source) => source,
+        Err(e) => return entry_error(e),
+    };
+    if tokio::fs::symlink_metadata(&source).await.is_err() {
+        return status(StatusCode::NOT_FOUND, "Not found");
+    }
+
+    // Destination is a full URL; only paths inside this mount are accepted
+    let Some(destination) = headers.get("Destination").and_then(|v| v.to_str().ok()) else {
+        return status(StatusCode::BAD_REQUEST, "Destination header required");
+    };
+    let dest_path = destination
+        .parse::<axum::http::Uri>()
+        .ok()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_default();
+    let Some(dest_path) = dest_path.strip_prefix(mount).filter(|p| p.is_empty() || p.starts_with('/')) else {
+        return status(StatusCode::FORBIDDEN, "Destination is outside this share");
+    };
+    let dest = match dav.root.resolve_entry(dest_path) {
+        Ok(dest) => dest,
+        Err(e) => return entry_error(e),
+    };
+    if dest.starts_with(&source) {
+        return status(StatusCode::FORBIDDEN, "Cannot copy or move a collection into itself");
+    }
+
+    let overwrite = headers
+        .get("Overwrite")
+        .is_none_or(|v| !v.as_bytes().eq_ignore_ascii_case(b"F"));
+    let existed = tokio::fs::symlink_metadata(&dest).await.is_ok();
+    if existed && !overwrite {
+        return status(StatusCode::PRECONDITION_FAILED, "Destination exists");
+    }
+    if existed {
+        if let Err(e) = remove(&dest).await {
+            return status(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string());
+        }
+    }
+
+    let result = if copy {
+        // Depth 0 copies a collection without its members
+        let recursive = headers.get("Depth").is_none_or(|v| v.as_bytes() != b"0");
+        tokio::task::spawn_blocking(move || copy_entry(&dav.root, &source, &dest, recursive))
+            .await
+            .unwrap_or_else(|e| Err(io::Error::other(e)))
+    } else {
+        tokio::fs::rename(&source, &dest).await
+    };
+    match result {
+        Ok(()) if existed => status(StatusCode::NO_CONTENT, ""),
+        Ok(()) => status(StatusCode::CREATED, ""),
+        Err(e) => status(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+/// Copy a file or collection. Members that the symlink policy hides are left
+/// out, and symlinked collections are not descended into.
+fn copy_entry(root: &SafeRoot, source: &Path, dest: &Path, recursive: bool) -> io::Result<()> {
+    if !fs::metadata(source)?.is_dir() {
+        return fs::copy(source, dest).map(|_| ());
+    }
+    fs::create_dir(dest)?;
+    if !recursive {
+        return Ok(());
+    }
+    let mut pending: Vec<(PathBuf, PathBuf)> = vec![(source.to_path_buf(), dest.to_path_buf())];
+    while let Some((from, to)) = pending.pop() {
+        for entry in fs::read_dir(&from)?.flatten() {
+            let path = entry.path();
+            if !root.permits(&path) {
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let target = to.join(entry.file_name());
+            if !metadata.is_dir() {
+                fs::copy(&path, &target)?;
+            } else if !entry.file_type()?.is_symlink() {
+                fs::create_dir(&target)?;
+                pending.push((path, target));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::paths::SymlinkPolicy;
+    use tower::ServiceExt;
+
+    /// root/{a.txt, sub/b.txt} next to an unshared outside/ directory
+    fn fixture() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("root/sub")).unwrap();
+        fs::create_dir(tmp.path().join("outside")).unwrap();
+        fs::write(tmp.path().join("root/a.txt"), "alpha").unwrap();
+        fs::write(tmp.path().join("root/sub/b.txt"), "beta").unwrap();
+        tmp
+    }
+
+    fn dav(tmp: &tempfile::TempDir, writable: bool) -> Router {
+        let root = SafeRoot::new(&tmp.path().join("root"), SymlinkPolicy::WithinRoot).unwrap();
+        router(root, WebDavConfig { writable, max_size: 8 })
+    }
+
+    async fn send(app: &Router, method: &str, uri: &str, headers: &[(&str, &str)], body: &'static str) -> Response {
+        let mut req = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        app.clone().oneshot(req.body(Body::from(body)).unwrap()).await.unwrap()
+    }
+
+    async fn text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn read_only_mounts_reject_every_write() {
+        let tmp = fixture();
+        let app = dav(&tmp, false);
+        let to_c = [("Destination", "http://example.com/dav/c.txt")];
+
+        for (method, uri, headers) in [
+            ("PUT", "/dav/new.txt", &[][..]),
+            ("PUT", "/dav/a.txt", &[]),
+            ("MKCOL", "/dav/made", &[]),
+            ("DELETE", "/dav/a.txt", &[]),
+            ("DELETE", "/dav/sub", &[]),
+            ("COPY", "/dav/a.txt", &to_c),
+            ("MOVE", "/dav/a.txt", &to_c),
+        ] {
+            let response = send(&app, method, uri, headers, "data").await;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{} {}", method, uri);
+        }
+
+        let response = send(&app, "OPTIONS", "/dav/", &[], "").await;
+        assert_eq!(response.headers()[header::ALLOW], "OPTIONS, GET, HEAD, PROPFIND");
+        let mut names: Vec<_> = fs::read_dir(tmp.path().join("root")).unwrap().flatten().map(|e| e.file_name()).collect();
+        names.sort();
+        assert_eq!(names, ["a.txt", "sub"]);
+        assert_eq!(fs::read_to_string(tmp.path().join("root/a.txt")).unwrap(), "alpha");
+    }
+
+    #[tokio::test]
+    async fn copy_and_move_stay_inside_the_share() {
+        let tmp = fixture();
+        let app = dav(&tmp, true);
+
+        for destination in [
+            "http://example.com/outside/a.txt",
+            "http://example.com/davish/a.txt",
+            "http://example.com/dav/../outside/a.txt",
+            "http://example.com/dav/sub/../../outside/a.txt",
+            "/dav/%2e%2e/outside/a.txt",
+        ] {
+            for method in ["COPY", "MOVE"] {
+                let response = send(&app, method, "/dav/a.txt", &[("Destination", destination)], "").await;
+                assert_eq!(response.status(), StatusCode::FORBIDDEN, "{} to {}", method, destination);
+            }
+        }
+        assert!(tmp.path().join("root/a.txt").is_file());
+        assert_eq!(fs::read_dir(tmp.path().join("outside")).unwrap().count(), 0);
+
+        let into_itself = [("Destination", "/dav/sub/inner")];
+        let response = send(&app, "COPY", "/dav/sub", &into_itself, "").await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = send(&app, "COPY", "/dav/sub", &[("Destination", "/dav/copy")], "").await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(fs::read_to_string(tmp.path().join("root/copy/b.txt")).unwrap(), "beta");
+        assert!(tmp.path().join("root/sub/b.txt").is_file());
+
+        let keep = [("Destination", "/dav/copy/b.txt"), ("Overwrite", "F")];
+        let response = send(&app, "MOVE", "/dav/a.txt", &keep, "").await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response = send(&app, "MOVE", "/dav/a.txt", &[("Destination", "/dav/copy/b.txt")], "").await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(fs::read_to_string(tmp.path().join("root/copy/b.txt")).unwrap(), "alpha");
+        assert!(!tmp.path().join("root/a.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn propfind_lists_one_level_at_most() {
+        let tmp = fixture();
+        let app = dav(&tmp, false);
+
+        let response = send(&app, "PROPFIND", "/dav/", &[("Depth", "0")], "").await;
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = text(response).await;
+        assert_eq!(body.matches("<D:response>").count(), 1);
+        assert!(body.contains("<D:href>/dav/</D:href>"));
+        assert!(body.contains("<D:collection/>"));
+
+        let body = text(send(&app, "PROPFIND", "/dav", &[("Depth", "1")], "").await).await;
+        assert_eq!(body.matches("<D:response>").count(), 3);
+        assert!(body.contains("<D:href>/dav/a.txt</D:href>"));
+        assert!(body.contains("<D:getcontentlength>5</D:getcontentlength>"));
+        assert!(body.contains("<D:href>/dav/sub/</D:href>"));
+        assert!(!body.contains("b.txt"));
+
+        let body = text(send(&app, "PROPFIND", "/dav/sub/b.txt", &[("Depth", "1")], "").await).await;
+        assert_eq!(body.matches("<D:response>").count(), 1);
+        assert!(body.contains("<D:href>/dav/sub/b.txt</D:href>"));
+        assert!(body.contains("<D:getcontenttype>text/plain</D:getcontenttype>"));
+
+        let response = send(&app, "PROPFIND", "/dav/", &[("Depth", "infinity")], "").await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = send(&app, "PROPFIND", "/dav/missing", &[("Depth", "0")], "").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn put_is_capped_like_uploads() {
+        let tmp = fixture();
+        let app = dav(&tmp, true);
+
+        let response = send(&app, "PUT", "/dav/small.txt", &[], "12345").await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response = send(&app, "PUT", "/dav/small.txt", &[], "123").await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(fs::read_to_string(tmp.path().join("root/small.txt")).unwrap(), "123");
+
+        // Refused up front when announced, cut off mid-stream when not
+        let response = send(&app, "PUT", "/dav/big.txt", &[("Content-Length", "10")], "0123456789").await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let response = send(&app, "PUT", "/dav/small.txt", &[], "0123456789").await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        assert!(!tmp.path().join("root/big.txt").exists());
+        assert_eq!(fs::read_to_string(tmp.path().join("root/small.txt")).unwrap(), "123");
+        assert_eq!(fs::read_dir(tmp.path().join("root")).unwrap().count(), 3);
+    }
+}
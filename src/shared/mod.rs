@@ -1,5 +1,22 @@
 use serde::{Deserialize, Serialize};
 
+/// "1.50 MB" and the like, for the TUI, listings and the dashboard
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+
+    if bytes >= GB {
+        format!("{:.2} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.2} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.2} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
 /// Stats for the file server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerStats {
@@ -13,7 +30,7 @@ pub struct ServerStats {
 }
 
 /// A single request log entry
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct RequestLog {
     pub timestamp: u64,
     pub method: String,
@@ -24,10 +41,71 @@ pub struct RequestLog {
     pub ip: String,
 }
 
-/// File stats for directory mode
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileStats {
+/// Kind of a listed entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    File,
+    Dir,
+}
+
+/// One entry of a JSON directory listing (`/api/ls`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirEntry {
     pub name: String,
-    pub requests: u64,
-    pub bytes_sent: u64,
+    /// Path from the share root, `/`-separated
+    pub path: String,
+    /// Percent-encoded URL path to download (or list) the entry
+    pub href: String,
+    #[serde(rename = "type")]
+    pub kind: EntryKind,
+    pub size: u64,
+    /// Modification time, seconds since the Unix epoch
+    pub mtime: Option<u64>,
+    /// Content type of files
+    pub mime: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
+}
+
+/// One page of a JSON directory listing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirListing {
+    /// Listed directory, from the share root
+    pub path: String,
+    pub recursive: bool,
+    /// Entries in the whole listing, not just this page
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<usize>,
+    /// Files on this page whose checksums were asked for but aren't ready yet
+    #[serde(default)]
+    pub hashes_pending: usize,
+    pub entries: Vec<DirEntry>,
+}
+
+/// One search hit; content searches also say where the text was found
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub entry: DirEntry,
+    /// 1-based line of the first content match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+/// Results of `/api/search`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResults {
+    /// Entries looked at
+    pub scanned: usize,
+    /// Stopped early at the result limit or the content-search budget
+    pub truncated: bool,
+    pub hits: Vec<SearchHit>,
 }
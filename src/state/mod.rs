@@ -0,0 +1,479 @@
+// 🗂️ Registry of running shares - one state file per share under ~/.yeet/shares
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::Write,
+    net::{IpAddr, TcpListener},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// First port tried when no --port is given
+const DEFAULT_PORT: u16 = 8000;
+/// How many ports above DEFAULT_PORT are probed before giving up
+const PORT_SCAN_RANGE: u16 = 100;
+/// Uploads remembered for the TUI and --status (drop-box mode)
+const RECENT_UPLOADS: usize = 20;
+
+/// A file received in drop-box mode
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReceivedFile {
+    pub name: String,
+    pub size: u64,
+    pub at: u64, // unix timestamp
+}
+
+/// How far a `yeet -` share has got through its input
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StdinProgress {
+    /// Bytes read from stdin so far
+    pub bytes: u64,
+    /// Stdin reached EOF
+    pub finished: bool,
+    /// Spooled to disk for any number of downloaders (--spool) rather than
+    /// streamed to the first one
+    pub spooled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TunnelState {
+    pub id: String,
+    /// Public URL, `None` until the tunnel has come up
+    pub url: Option<String>,
+    /// Dashboard URL including the admin credential
+    pub admin_url: Option<String>,
+    pub pid: u32,
+    pub port: u16,
+    pub file_path: String,
+    /// Tunnel provider serving the share (cloudflared, ssh, lan, ...)
+    #[serde(default)]
+    pub tunnel: String,
+    /// Times the tunnel helper had to be restarted
+    #[serde(default)]
+    pub tunnel_restarts: u32,
+    /// Why the tunnel was last restarted
+    #[serde(default)]
+    pub tunnel_error: Option<String>,
+    pub created_at: u64, // unix timestamp
+    /// Unix timestamp after which the share shuts itself down (--expire)
+    pub expires_at: Option<u64>,
+    /// Shutdown after this long without requests (--idle-timeout)
+    pub idle_timeout_secs: Option<u64>,
+    /// Downloads allowed before shutdown (--max-downloads)
+    pub max_downloads: Option<u64>,
+    pub downloads: u64,
+    /// Why the daemon shut itself down, if it did
+    pub ended_reason: Option<String>,
+    /// Drop-box share accepting uploads (--receive)
+    #[serde(default)]
+    pub receive: bool,
+    /// Most recent uploads, newest last
+    #[serde(default)]
+    pub received: Vec<ReceivedFile>,
+    #[serde(default)]
+    pub received_total: u64,
+    /// Reverse proxy to a local server; `file_path` holds its URL
+    #[serde(default)]
+    pub proxy: bool,
+    /// Sharing piped input (`yeet -`); `file_path` holds the download name
+    #[serde(default)]
+    pub stdin: Option<StdinProgress>,
+    /// Live tail of a growing file (--follow); `/` is the live view
+    #[serde(default)]
+    pub follow: bool,
+    /// Checksums of a single shared file, once hashed
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub blake3: Option<String>,
+    /// Fingerprint of the options the share was started with; reuse needs a match
+    #[serde(default)]
+    pub settings: Option<String>,
+}
+
+impl TunnelState {
+    pub fn new(id: String, pid: u32, port: u16, file_path: String) -> Self {
+        Self {
+            id,
+            url: None,
+            admin_url: None,
+            pid,
+            port,
+            file_path,
+            tunnel: String::new(),
+            tunnel_restarts: 0,
+            tunnel_error: None,
+            created_at: unix_now(),
+            expires_at: None,
+            idle_timeout_secs: None,
+            max_downloads: None,
+            downloads: 0,
+            ended_reason: None,
+            receive: false,
+            received: Vec::new(),
+            received_total: 0,
+            proxy: false,
+            stdin: None,
+            follow: false,
+            sha256: None,
+            blake3: None,
+            settings: None,
+        }
+    }
+
+    fn yeet_dir() -> PathBuf {
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home).join(".yeet")
+    }
+
+    fn shares_dir() -> PathBuf {
+        let dir = Self::yeet_dir().join("shares");
+        fs::create_dir_all(&dir).ok();
+        dir
+    }
+
+    fn state_file(id: &str) -> PathBuf {
+        Self::shares_dir().join(format!("{}.state", id))
+    }
+
+    pub fn load(id: &str) -> Option<Self> {
+        Self::load_from(&Self::shares_dir(), id)
+    }
+
+    fn load_from(dir: &Path, id: &str) -> Option<Self> {
+        let content = fs::read_to_string(dir.join(format!("{}.state", id))).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// All registered shares, oldest first
+    pub fn all() -> Vec<Self> {
+        Self::all_in(&Self::shares_dir())
+    }
+
+    fn all_in(dir: &Path) -> Vec<Self> {
+        let mut shares: Vec<Self> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| e.path().extension().is_some_and(|ext| ext == "state"))
+                    .filter_map(|e| fs::read_to_string(e.path()).ok())
+                    .filter_map(|content| serde_json::from_str(&content).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        shares.sort_by_key(|s: &Self| s.created_at);
+        shares
+    }
+
+    /// Look a share up by its id or an unambiguous id prefix
+    pub fn find(id: &str) -> Option<Self> {
+        Self::find_in(&Self::shares_dir(), id)
+    }
+
+    fn find_in(dir: &Path, id: &str) -> Option<Self> {
+        if let Some(state) = Self::load_from(dir, id) {
+            return Some(state);
+        }
+        let mut matches = Self::all_in(dir).into_iter().filter(|s| s.id.starts_with(id));
+        match (matches.next(), matches.next()) {
+            (Some(state), None) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        // Write-then-rename so readers never see a half-written file
+        let path = Self::state_file(&self.id);
+        let tmp = path.with_extension("state.tmp");
+        write_private(&tmp, serde_json::to_string_pretty(self)?.as_bytes())?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn delete(&self) {
+        let _ = fs::remove_file(Self::state_file(&self.id));
+        let _ = fs::remove_dir_all(spool_dir().join(&self.id));
+    }
+
+    pub fn is_tunnel_alive(&self) -> bool {
+        // Check if the process is still running
+        #[cfg(unix)]
+        {
+            nix::sys::signal::kill(nix::unistd::Pid::from_raw(self.pid as i32), None).is_ok()
+        }
+        #[cfg(not(unix))]
+        {
+            // Windows fallback - always assume dead
+            false
+        }
+    }
+
+    /// Stop the daemon and everything in its process group (cloudflared)
+    pub fn stop(&self) -> Result<()> {
+        #[cfg(unix)]
+        {
+            nix::sys::signal::killpg(
+                nix::unistd::Pid::from_raw(self.pid as i32),
+                nix::sys::signal::Signal::SIGTERM,
+            )?;
+            Ok(())
+        }
+        #[cfg(not(unix))]
+        {
+            anyhow::bail!("Kill not supported on this platform")
+        }
+    }
+
+    pub fn age_hours(&self) -> f64 {
+        unix_now().saturating_sub(self.created_at) as f64 / 3600.0
+    }
+
+    /// Seconds left before --expire kicks in
+    pub fn remaining_secs(&self) -> Option<u64> {
+        self.expires_at.map(|at| at.saturating_sub(unix_now()))
+    }
+
+    pub fn downloads_left(&self) -> Option<u64> {
+        self.max_downloads.map(|max| max.saturating_sub(self.downloads))
+    }
+
+    /// One-line summary of the expiry limits, e.g. "1h 59m left · 2 of 5 downloads left"
+    pub fn limits_summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(secs) = self.remaining_secs() {
+            parts.push(format!("{} left", format_duration(secs)));
+        }
+        if let Some(idle) = self.idle_timeout_secs {
+            parts.push(format!("idle timeout {}", format_duration(idle)));
+        }
+        if let (Some(left), Some(max)) = (self.downloads_left(), self.max_downloads) {
+            parts.push(format!("{} of {} downloads left", left, max));
+        }
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
+    pub fn record_upload(&mut self, file: ReceivedFile) {
+        self.received.push(file);
+        if self.received.len() > RECENT_UPLOADS {
+            self.received.remove(0);
+        }
+        self.received_total += 1;
+    }
+
+    /// Restart count and last error, e.g. "restarted 2x · last error: cloudflared exited"
+    pub fn tunnel_summary(&self) -> Option<String> {
+        if self.tunnel_restarts == 0 {
+            return None;
+        }
+        Some(match &self.tunnel_error {
+            Some(error) => format!("restarted {}x · last error: {}", self.tunnel_restarts, error),
+            None => format!("restarted {}x", self.tunnel_restarts),
+        })
+    }
+}
+
+/// Partial resumable (tus) uploads, kept across daemon restarts
+pub fn uploads_dir() -> PathBuf {
+    let dir = TunnelState::yeet_dir().join("uploads");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Spooled stdin of `yeet - --spool` shares, one directory per share id
+pub fn spool_dir() -> PathBuf {
+    TunnelState::yeet_dir().join("spool")
+}
+
+/// Per-install random key for fingerprinting share options, so the
+/// fingerprints kept in state files can't be used to guess passwords
+pub fn settings_key() -> [u8; 32] {
+    let path = TunnelState::yeet_dir().join("settings.key");
+    if let Some(key) = fs::read(&path).ok().and_then(|bytes| bytes.try_into().ok()) {
+        return key;
+    }
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key).expect("Failed to gather randomness");
+    fs::create_dir_all(TunnelState::yeet_dir()).ok();
+    let _ = write_private(&path, &key);
+    key
+}
+
+/// Daemon-side handle on its own state; every update is persisted at once
+#[derive(Clone)]
+pub struct StateHandle {
+    state: Arc<Mutex<TunnelState>>,
+    persist: bool,
+}
+
+impl StateHandle {
+    pub fn new(state: TunnelState) -> Self {
+        let _ = state.save();
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            persist: true,
+        }
+    }
+
+    /// Handle that never touches `~/.yeet`, for handler tests
+    #[cfg(test)]
+    pub fn in_memory(state: TunnelState) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            persist: false,
+        }
+    }
+
+    pub fn port(&self) -> u16 {
+        self.state.lock().unwrap().port
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut TunnelState)) {
+        let mut state = self.state.lock().unwrap();
+        f(&mut state);
+        if self.persist {
+            let _ = state.save();
+        }
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Compact human duration: "45s", "12m", "2h 5m", "3d 4h"
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+        _ => format!("{}d {}h", secs / 86400, (secs % 86400) / 3600),
+    }
+}
+
+/// Write a file only the current user can read: state files hold the admin
+/// token and secret links
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        let file = options.open(path)?;
+        // `mode` only applies to new files; a leftover temp file keeps its old one
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        (&file).write_all(contents)
+    }
+    #[cfg(not(unix))]
+    {
+        options.open(path)?.write_all(contents)
+    }
+}
+
+/// Short random id used to address a share (`yeet stop <id>`)
+pub fn new_share_id() -> String {
+    loop {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos(),
+        );
+        let id = format!("{:06x}", hasher.finish() & 0xff_ffff);
+        if TunnelState::load(&id).is_none() {
+            return id;
+        }
+    }
+}
+
+/// Choose the port for a new share that will listen on `bind`. An explicit
+/// port must be free; otherwise the first free port from DEFAULT_PORT
+/// upwards not claimed by a live share.
+pub fn pick_port(requested: Option<u16>, bind: IpAddr) -> Result<u16> {
+    let claimed: Vec<u16> = TunnelState::all()
+        .iter()
+        .filter(|s| s.is_tunnel_alive())
+        .map(|s| s.port)
+        .collect();
+    choose_port(requested, &claimed, bind, DEFAULT_PORT..DEFAULT_PORT.saturating_add(PORT_SCAN_RANGE))
+}
+
+fn choose_port(requested: Option<u16>, claimed: &[u16], bind: IpAddr, candidates: Range<u16>) -> Result<u16> {
+    let is_free = |port: u16| !claimed.contains(&port) && TcpListener::bind((bind, port)).is_ok();
+    if let Some(port) = requested {
+        if !is_free(port) {
+            anyhow::bail!("Port {} is already in use", port);
+        }
+        return Ok(port);
+    }
+
+    let (first, last) = (candidates.start, candidates.end.saturating_sub(1));
+    candidates
+        .into_iter()
+        .find(|port| is_free(*port))
+        .ok_or_else(|| anyhow::anyhow!("No free port found in {}-{}", first, last))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn state_files_are_private() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("abc.state.tmp");
+        fs::write(&path, "old, world-readable").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, b"{}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn finds_shares_by_id_prefix() {
+        let tmp = tempfile::tempdir().unwrap();
+        for id in ["abc123", "abd456", "xyz789"] {
+            let state = TunnelState::new(id.to_string(), 1, 8000, "f".to_string());
+            fs::write(tmp.path().join(format!("{}.state", id)), serde_json::to_string(&state).unwrap()).unwrap();
+        }
+        fs::write(tmp.path().join("broken.state"), "{").unwrap();
+
+        let found = |id| TunnelState::find_in(tmp.path(), id).map(|s| s.id);
+        assert_eq!(found("abc123").as_deref(), Some("abc123"));
+        assert_eq!(found("abc").as_deref(), Some("abc123"));
+        assert_eq!(found("x").as_deref(), Some("xyz789"));
+        assert_eq!(found("ab"), None, "ambiguous");
+        assert_eq!(found("nope"), None);
+        assert_eq!(TunnelState::all_in(tmp.path()).len(), 3);
+    }
+
+    #[test]
+    fn skips_ports_in_use() {
+        let localhost = IpAddr::from([127, 0, 0, 1]);
+        let taken = TcpListener::bind((localhost, 0)).unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let candidates = port..port.saturating_add(3);
+
+        assert!(choose_port(Some(port), &[], localhost, candidates.clone()).is_err());
+        let chosen = choose_port(None, &[], localhost, candidates.clone()).unwrap();
+        assert_ne!(chosen, port);
+        let next = choose_port(None, &[chosen], localhost, candidates.clone()).unwrap();
+        assert!(next != port && next != chosen);
+        assert!(choose_port(None, &[], localhost, port..port + 1).is_err());
+
+        // A LAN share binds every interface, so a loopback-only listener still counts
+        let everywhere = IpAddr::from([0, 0, 0, 0]);
+        assert!(choose_port(Some(port), &[], everywhere, candidates).is_err());
+    }
+}
@@ -0,0 +1,518 @@
+// 🌍 Tunnel providers - how the local server gets a public (or LAN) URL
+pub mod supervisor;
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, UdpSocket},
+    path::PathBuf,
+    process::Stdio,
+    sync::Arc,
+};
+
+use regex::Regex;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::{Child, Command},
+    sync::mpsc,
+};
+
+/// Something a provider noticed about its tunnel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TunnelEvent {
+    /// Public base URL (no trailing slash) is known
+    Url(String),
+    /// The tunnel reported a working connection
+    Healthy,
+    /// The tunnel reported a lost/failed connection
+    Unhealthy(String),
+    /// The helper process is gone
+    Exited(String),
+}
+
+/// A way of exposing the local server
+pub trait TunnelProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Helper process to run, or `None` if no process is needed
+    fn command(&self, port: u16) -> Option<std::process::Command>;
+
+    /// URL known up front (configured hostname, LAN address)
+    fn static_url(&self, _port: u16) -> Option<String> {
+        None
+    }
+
+    /// Interpret one line of the helper's stdout/stderr
+    fn parse_line(&self, line: &str) -> Option<TunnelEvent>;
+
+    /// Address the HTTP server listens on
+    fn bind_addr(&self) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::LOCALHOST)
+    }
+
+    /// Header this tunnel's edge sets to the real client address
+    fn client_ip_header(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// Provider selection for `--tunnel`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TunnelKind {
+    /// Cloudflare quick tunnel (random trycloudflare.com URL)
+    #[default]
+    Cloudflare,
+    /// Pre-configured named Cloudflare tunnel (--tunnel-name, --hostname)
+    CloudflareNamed,
+    /// SSH reverse tunnel to a host you control (--ssh-target)
+    Ssh,
+    /// No tunnel: serve on the local network only
+    Lan,
+}
+
+/// Provider-specific command line options
+#[derive(Debug, Clone, Default)]
+pub struct TunnelOptions {
+    pub kind: TunnelKind,
+    pub tunnel_name: Option<String>,
+    pub hostname: Option<String>,
+    pub ssh_target: Option<String>,
+    pub ssh_remote_port: Option<u16>,
+    pub public_url: Option<String>,
+}
+
+/// Build the provider, validating the options it needs
+pub fn build(options: &TunnelOptions) -> anyhow::Result<Arc<dyn TunnelProvider>> {
+    Ok(match options.kind {
+        TunnelKind::Cloudflare => Arc::new(CloudflareQuick::default()),
+        TunnelKind::CloudflareNamed => {
+            let name = options
+                .tunnel_name
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("--tunnel cloudflare-named needs --tunnel-name"))?;
+            let hostname = options
+                .hostname
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("--tunnel cloudflare-named needs --hostname"))?;
+            Arc::new(CloudflareNamed {
+                binary: PathBuf::from("cloudflared"),
+                name,
+                hostname,
+            })
+        }
+        TunnelKind::Ssh => {
+            let target = options
+                .ssh_target
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("--tunnel ssh needs --ssh-target user@host"))?;
+            Arc::new(SshReverse {
+                binary: PathBuf::from("ssh"),
+                remote_port: options.ssh_remote_port.unwrap_or(8080),
+                public_url: options.public_url.clone(),
+                target,
+            })
+        }
+        TunnelKind::Lan => Arc::new(Lan),
+    })
+}
+
+/// `cloudflared tunnel --url` with a random trycloudflare.com hostname
+pub struct CloudflareQuick {
+    pub binary: PathBuf,
+    url_re: Regex,
+}
+
+impl Default for CloudflareQuick {
+    fn default() -> Self {
+        Self::with_binary(PathBuf::from("cloudflared"))
+    }
+}
+
+impl CloudflareQuick {
+    pub fn with_binary(binary: PathBuf) -> Self {
+        Self {
+            binary,
+            url_re: Regex::new(r"https://[^\s|]+\.trycloudflare\.com").unwrap(),
+        }
+    }
+}
+
+impl TunnelProvider for CloudflareQuick {
+    fn name(&self) -> &'static str {
+        "cloudflared"
+    }
+
+    fn client_ip_header(&self) -> Option<&'static str> {
+        Some("cf-connecting-ip")
+    }
+
+    fn command(&self, port: u16) -> Option<std::process::Command> {
+        let mut cmd = std::process::Command::new(&self.binary);
+        cmd.args(["tunnel", "--url", &format!("http://localhost:{}", port)]);
+        Some(cmd)
+    }
+
+    fn parse_line(&self, line: &str) -> Option<TunnelEvent> {
+        if let Some(m) = self.url_re.find(line) {
+            return Some(TunnelEvent::Url(m.as_str().to_string()));
+        }
+        cloudflared_health(line)
+    }
+}
+
+/// `cloudflared tunnel run <name>` for a tunnel with a routed hostname
+pub struct CloudflareNamed {
+    pub binary: PathBuf,
+    pub name: String,
+    pub hostname: String,
+}
+
+impl TunnelProvider for CloudflareNamed {
+    fn name(&self) -> &'static str {
+        "cloudflared (named)"
+    }
+
+    fn client_ip_header(&self) -> Option<&'static str> {
+        Some("cf-connecting-ip")
+    }
+
+    fn command(&self, port: u16) -> Option<std::process::Command> {
+        let mut cmd = std::process::Command::new(&self.binary);
+        cmd.args(["tunnel", "run", "--url", &format!("http://localhost:{}", port), &self.name]);
+        Some(cmd)
+    }
+
+    fn static_url(&self, _port: u16) -> Option<String> {
+        let host = self.hostname.trim_end_matches('/');
+        Some(if host.starts_with("http://") || host.starts_with("https://") {
+            host.to_string()
+        } else {
+            format!("https://{}", host)
+        })
+    }
+
+    fn parse_line(&self, line: &str) -> Option<TunnelEvent> {
+        cloudflared_health(line)
+    }
+}
+
+/// Connection state from cloudflared's log output
+fn cloudflared_health(line: &str) -> Option<TunnelEvent> {
+    if line.contains("Registered tunnel connection") {
+        Some(TunnelEvent::Healthy)
+    } else if line.contains("Unregistered tunnel connection")
+        || line.contains("Connection terminated")
+        || line.contains("failed to connect to the edge")
+        || line.contains("Retrying connection")
+    {
+        Some(TunnelEvent::Unhealthy(line.trim().to_string()))
+    } else {
+        None
+    }
+}
+
+/// `ssh -R` to a bastion; the URL is either given or derived from the host
+pub struct SshReverse {
+    pub binary: PathBuf,
+    pub target: String,
+    pub remote_port: u16,
+    pub public_url: Option<String>,
+}
+
+impl TunnelProvider for SshReverse {
+    fn name(&self) -> &'static str {
+        "ssh"
+    }
+
+    fn command(&self, port: u16) -> Option<std::process::Command> {
+        let mut cmd = std::process::Command::new(&self.binary);
+        // -v for the "remote forward success" line, ssh's only sign of life
+        cmd.args([
+            "-N",
+            "-v",
+            "-o",
+            "ExitOnForwardFailure=yes",
+            "-o",
+            "ServerAliveInterval=15",
+            "-o",
+            "ServerAliveCountMax=3",
+            "-o",
+            "BatchMode=yes",
+            "-R",
+            &format!("{}:localhost:{}", self.remote_port, port),
+            &self.target,
+        ]);
+        Some(cmd)
+    }
+
+    fn static_url(&self, _port: u16) -> Option<String> {
+        if let Some(url) = &self.public_url {
+            return Some(url.trim_end_matches('/').to_string());
+        }
+        let host = self.target.rsplit('@').next().unwrap_or(&self.target);
+        let host = host.split(':').next().unwrap_or(host);
+        Some(format!("http://{}:{}", host, self.remote_port))
+    }
+
+    fn parse_line(&self, line: &str) -> Option<TunnelEvent> {
+        let lower = line.to_ascii_lowercase();
+        if lower.contains("remote forward success") || lower.starts_with("allocated port") {
+            return Some(TunnelEvent::Healthy);
+        }
+        if lower.starts_with("debug") {
+            // Per-connection chatter from -v
+            return None;
+        }
+        if lower.contains("remote port forwarding failed")
+            || lower.contains("connection closed")
+            || lower.contains("broken pipe")
+            || lower.contains("timeout, server")
+            || lower.contains("permission denied")
+        {
+            Some(TunnelEvent::Unhealthy(line.trim().to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+/// No tunnel at all: listen on every interface and advertise the LAN address
+pub struct Lan;
+
+impl TunnelProvider for Lan {
+    fn name(&self) -> &'static str {
+        "lan"
+    }
+
+    fn command(&self, _port: u16) -> Option<std::process::Command> {
+        None
+    }
+
+    fn static_url(&self, port: u16) -> Option<String> {
+        Some(format!("http://{}:{}", lan_ip(), port))
+    }
+
+    fn parse_line(&self, _line: &str) -> Option<TunnelEvent> {
+        None
+    }
+
+    fn bind_addr(&self) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    }
+}
+
+/// Address of the interface holding the default route (no packets are sent)
+fn lan_ip() -> IpAddr {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("192.0.2.1:9")?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+/// A running tunnel: the helper process plus its parsed output
+pub struct Tunnel {
+    provider: Arc<dyn TunnelProvider>,
+    child: Option<Child>,
+    lines: Option<mpsc::UnboundedReceiver<String>>,
+    pending_url: Option<String>,
+}
+
+impl Tunnel {
+    pub fn start(provider: Arc<dyn TunnelProvider>, port: u16) -> io::Result<Self> {
+        let pending_url = provider.static_url(port);
+        let (child, lines) = match provider.command(port) {
+            Some(cmd) => {
+                let mut cmd = Command::from(cmd);
+                cmd.stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .kill_on_drop(true);
+                let mut child = cmd.spawn()?;
+
+                // Both streams feed one channel; it closes once both hit EOF
+                let (tx, rx) = mpsc::unbounded_channel();
+                if let Some(stdout) = child.stdout.take() {
+                    tokio::spawn(forward_lines(stdout, tx.clone()));
+                }
+                if let Some(stderr) = child.stderr.take() {
+                    tokio::spawn(forward_lines(stderr, tx));
+                }
+                (Some(child), Some(rx))
+            }
+            None => (None, None),
+        };
+
+        Ok(Self {
+            provider,
+            child,
+            lines,
+            pending_url,
+        })
+    }
+
+    /// Wait for the next thing worth reacting to
+    pub async fn next_event(&mut self) -> TunnelEvent {
+        if let Some(url) = self.pending_url.take() {
+            return TunnelEvent::Url(url);
+        }
+
+        let Some(lines) = self.lines.as_mut() else {
+            // Process-less providers never change
+            return std::future::pending().await;
+        };
+
+        while let Some(line) = lines.recv().await {
+            if let Some(event) = self.provider.parse_line(&line) {
+                return event;
+            }
+        }
+
+        self.lines = None;
+        let status = match self.child.as_mut() {
+            Some(child) => child
+                .wait()
+                .await
+                .map(|s| s.to_string())
+                .unwrap_or_else(|e| e.to_string()),
+            None => "no process".to_string(),
+        };
+        TunnelEvent::Exited(format!("{} exited ({})", self.provider.name(), status))
+    }
+
+    /// Terminate the helper process
+    pub async fn stop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            #[cfg(unix)]
+            if let Some(pid) = child.id() {
+                // SIGTERM first so cloudflared can unregister cleanly
+                let _ = nix::sys::signal::kill(
+                    nix::unistd::Pid::from_raw(pid as i32),
+                    nix::sys::signal::Signal::SIGTERM,
+                );
+                if tokio::time::timeout(std::time::Duration::from_secs(3), child.wait()).await.is_ok() {
+                    return;
+                }
+            }
+            let _ = child.kill().await;
+        }
+    }
+}
+
+async fn forward_lines<R: AsyncRead + Unpin>(reader: R, tx: mpsc::UnboundedSender<String>) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if tx.send(line).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Write an executable shell script standing in for a provider binary
+    fn fake_binary(dir: &tempfile::TempDir, name: &str, script: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn cloudflare_quick_discovers_url_and_health() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = fake_binary(
+            &dir,
+            "cloudflared",
+            r#"[ "$1 $2 $3" = "tunnel --url http://localhost:9999" ] || exit 3
+echo "INF |  https://fuzzy-cat-1234.trycloudflare.com  |" >&2
+echo "INF Registered tunnel connection connIndex=0" >&2
+echo "ERR Connection terminated error=\"timeout\"" >&2"#,
+        );
+
+        let provider = Arc::new(CloudflareQuick::with_binary(binary));
+        let mut tunnel = Tunnel::start(provider, 9999).unwrap();
+
+        assert_eq!(
+            tunnel.next_event().await,
+            TunnelEvent::Url("https://fuzzy-cat-1234.trycloudflare.com".to_string())
+        );
+        assert_eq!(tunnel.next_event().await, TunnelEvent::Healthy);
+        assert!(matches!(tunnel.next_event().await, TunnelEvent::Unhealthy(_)));
+        match tunnel.next_event().await {
+            TunnelEvent::Exited(reason) => assert!(reason.starts_with("cloudflared exited")),
+            other => panic!("expected exit, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn ssh_uses_static_url_and_reports_forward_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = fake_binary(
+            &dir,
+            "ssh",
+            r#"for arg; do last="$arg"; done
+[ "$last" = "deploy@bastion.example.com" ] || exit 3
+echo "Error: remote port forwarding failed for listen port 8443" >&2
+exit 255"#,
+        );
+
+        let provider = Arc::new(SshReverse {
+            binary,
+            target: "deploy@bastion.example.com".to_string(),
+            remote_port: 8443,
+            public_url: None,
+        });
+        let mut tunnel = Tunnel::start(provider, 9999).unwrap();
+
+        assert_eq!(
+            tunnel.next_event().await,
+            TunnelEvent::Url("http://bastion.example.com:8443".to_string())
+        );
+        assert!(matches!(tunnel.next_event().await, TunnelEvent::Unhealthy(_)));
+        assert!(matches!(tunnel.next_event().await, TunnelEvent::Exited(_)));
+    }
+
+    #[test]
+    fn ssh_reports_forward_success() {
+        let ssh = SshReverse {
+            binary: PathBuf::from("ssh"),
+            target: "bastion".to_string(),
+            remote_port: 8443,
+            public_url: None,
+        };
+        let healthy = [
+            "debug1: remote forward success for: listen 8443, connect localhost:9999",
+            "Allocated port 40123 for remote forward to localhost:9999",
+        ];
+        for line in healthy {
+            assert_eq!(ssh.parse_line(line), Some(TunnelEvent::Healthy), "{}", line);
+        }
+        assert_eq!(ssh.parse_line("debug1: channel 1: connection closed by peer"), None);
+        assert!(matches!(ssh.parse_line("Connection closed by 192.0.2.7 port 22"), Some(TunnelEvent::Unhealthy(_))));
+    }
+
+    #[tokio::test]
+    async fn missing_binary_fails_to_start() {
+        let provider = Arc::new(CloudflareQuick::with_binary(PathBuf::from("/nonexistent/cloudflared")));
+        assert!(Tunnel::start(provider, 9999).is_err());
+    }
+
+    #[test]
+    fn named_and_lan_urls() {
+        let named = CloudflareNamed {
+            binary: PathBuf::from("cloudflared"),
+            name: "yeet".to_string(),
+            hostname: "files.example.com".to_string(),
+        };
+        assert_eq!(named.static_url(8000).as_deref(), Some("https://files.example.com"));
+
+        let lan = Lan.static_url(8123).unwrap();
+        assert!(lan.starts_with("http://") && lan.ends_with(":8123"));
+        assert_eq!(Lan.bind_addr(), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    }
+}
@@ -0,0 +1,200 @@
+// 🩺 Keeps the tunnel helper alive: restarts it with backoff when it exits
+// or stays disconnected, and reports URL changes along the way
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio_util::sync::CancellationToken;
+
+use super::{Tunnel, TunnelEvent, TunnelProvider};
+
+/// Delay before the first restart; doubled after every quick failure
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A run lasting this long counts as stable and resets the backoff
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// How long a lost connection may take to recover on its own
+const UNHEALTHY_GRACE: Duration = Duration::from_secs(30);
+/// Give up after this many starts in a row that never came up
+const MAX_FAILED_STARTS: u32 = 5;
+
+/// What the supervisor has to tell its owner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// Public base URL, sent again whenever it changes
+    Url(String),
+    /// The helper died or lost its connection and is being restarted
+    Restarting { restarts: u32, error: String },
+    /// Restarting is pointless (can't spawn, or keeps failing)
+    Failed(String),
+}
+
+/// Run the provider until `shutdown` fires or it can't be kept alive
+pub async fn supervise(
+    provider: Arc<dyn TunnelProvider>,
+    port: u16,
+    shutdown: CancellationToken,
+    mut report: impl FnMut(Status),
+) {
+    let mut restarts = 0;
+    let mut failed_starts = 0;
+    let mut backoff = INITIAL_BACKOFF;
+    let mut current_url: Option<String> = None;
+
+    loop {
+        let mut tunnel = match Tunnel::start(provider.clone(), port) {
+            Ok(tunnel) => tunnel,
+            Err(e) => {
+                report(Status::Failed(format!("failed to start {}: {}", provider.name(), e)));
+                return;
+            }
+        };
+
+        let started = Instant::now();
+        // A URL alone proves nothing (static URLs are known before the helper
+        // even connects): only a healthy report or a long enough run counts
+        let mut came_up = false;
+        let mut unhealthy_since: Option<Instant> = None;
+        let mut last_problem: Option<String> = None;
+
+        let error = loop {
+            // Only wake up for the grace period while the connection is down
+            let grace = async {
+                match unhealthy_since {
+                    Some(since) => tokio::time::sleep_until((since + UNHEALTHY_GRACE).into()).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    tunnel.stop().await;
+                    return;
+                }
+                _ = grace => {
+                    tunnel.stop().await;
+                    break format!(
+                        "no connection for {}s: {}",
+                        UNHEALTHY_GRACE.as_secs(),
+                        last_problem.take().unwrap_or_default()
+                    );
+                }
+                event = tunnel.next_event() => match event {
+                    TunnelEvent::Url(url) => {
+                        if current_url.as_ref() != Some(&url) {
+                            current_url = Some(url.clone());
+                            report(Status::Url(url));
+                        }
+                    }
+                    TunnelEvent::Healthy => {
+                        came_up = true;
+                        unhealthy_since = None;
+                    }
+                    TunnelEvent::Unhealthy(problem) => {
+                        unhealthy_since.get_or_insert_with(Instant::now);
+                        last_problem = Some(problem);
+                    }
+                    TunnelEvent::Exited(reason) => {
+                        break match last_problem.take() {
+                            Some(problem) => format!("{}: {}", reason, problem),
+                            None => reason,
+                        };
+                    }
+                },
+            }
+        };
+
+        if came_up || started.elapsed() >= STABLE_AFTER {
+            failed_starts = 0;
+        } else {
+            failed_starts += 1;
+            if failed_starts >= MAX_FAILED_STARTS {
+                report(Status::Failed(format!("gave up after {} failed starts: {}", failed_starts, error)));
+                return;
+            }
+        }
+        if started.elapsed() >= STABLE_AFTER {
+            backoff = INITIAL_BACKOFF;
+        }
+
+        restarts += 1;
+        report(Status::Restarting { restarts, error });
+
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = tokio::time::sleep(backoff) => {}
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tunnel::CloudflareQuick;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[tokio::test]
+    async fn restarts_and_reports_new_hostname() {
+        let dir = tempfile::tempdir().unwrap();
+        let counter = dir.path().join("runs");
+        let binary = dir.path().join("cloudflared");
+        // Each run gets a new hostname and dies right away, like a dropped quick tunnel
+        std::fs::write(
+            &binary,
+            format!(
+                "#!/bin/sh\necho x >> '{0}'\nn=$(wc -l < '{0}' | tr -d ' ')\necho \"INF https://run-$n.trycloudflare.com\" >&2\n",
+                counter.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let shutdown = CancellationToken::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::spawn(supervise(
+            Arc::new(CloudflareQuick::with_binary(binary)),
+            9999,
+            shutdown.clone(),
+            move |status| {
+                let _ = tx.send(status);
+            },
+        ));
+
+        assert_eq!(rx.recv().await, Some(Status::Url("https://run-1.trycloudflare.com".to_string())));
+        assert!(matches!(rx.recv().await, Some(Status::Restarting { restarts: 1, .. })));
+        assert_eq!(rx.recv().await, Some(Status::Url("https://run-2.trycloudflare.com".to_string())));
+
+        shutdown.cancel();
+        task.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn static_urls_alone_dont_count_as_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("ssh");
+        // Connects, then dies without ever confirming the forward
+        std::fs::write(&binary, "#!/bin/sh\necho 'debug1: Authenticated to bastion' >&2\nexit 255\n").unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let provider = Arc::new(crate::tunnel::SshReverse {
+            binary,
+            target: "bastion".to_string(),
+            remote_port: 8443,
+            public_url: None,
+        });
+
+        let mut statuses = Vec::new();
+        supervise(provider, 9999, CancellationToken::new(), |status| statuses.push(status)).await;
+        assert_eq!(statuses.first(), Some(&Status::Url("http://bastion:8443".to_string())));
+        assert!(matches!(statuses.last(), Some(Status::Failed(reason)) if reason.contains("gave up after 5")));
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_binary_is_missing() {
+        let provider = Arc::new(CloudflareQuick::with_binary("/nonexistent/cloudflared".into()));
+        let mut statuses = Vec::new();
+        supervise(provider, 9999, CancellationToken::new(), |status| statuses.push(status)).await;
+        assert!(matches!(statuses.as_slice(), [Status::Failed(_)]));
+    }
+}